@@ -0,0 +1,62 @@
+//! Heap used by the coolstore report with and without string interning.
+//!
+//! Run with `cargo bench --bench memory`. A counting allocator records the
+//! bytes alive after each step, so the numbers are exact for this build.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use kai::yaml_parser::{AnalysisReport, Ruleset};
+
+struct Counting;
+
+static LIVE: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        LIVE.fetch_add(layout.size(), Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        LIVE.fetch_sub(layout.size(), Ordering::Relaxed);
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: Counting = Counting;
+
+// Bytes still allocated after `build` returns, for as long as its result lives.
+fn measure<T>(build: impl FnOnce() -> T) -> (T, usize) {
+    let before = LIVE.load(Ordering::Relaxed);
+    let value = build();
+    (value, LIVE.load(Ordering::Relaxed).saturating_sub(before))
+}
+
+fn mib(bytes: usize) -> String {
+    format!("{:.2} MiB", bytes as f64 / (1024.0 * 1024.0))
+}
+
+fn main() {
+    let yaml = std::fs::read_to_string("samples/coolstore_analysis_output.yaml").unwrap();
+
+    let parse = || AnalysisReport { rulesets: serde_yaml::from_str::<Vec<Ruleset>>(&yaml).unwrap() };
+
+    // Both reports are parsed from scratch, so neither shares strings with the other.
+    let (plain, plain_bytes) = measure(parse);
+    let (interned, interned_bytes) = measure(|| {
+        let mut report = parse();
+        report.intern();
+        report
+    });
+    assert_eq!(plain, interned);
+    drop(plain);
+    let (index, index_bytes) = measure(|| interned.impacted_files());
+
+    println!("coolstore report, {} incidents", interned.summary().incidents);
+    println!("  parsed:          {}", mib(plain_bytes));
+    println!("  interned:        {}", mib(interned_bytes));
+    println!("  saved:           {} ({:.1}%)", mib(plain_bytes - interned_bytes),
+        100.0 * (plain_bytes - interned_bytes) as f64 / plain_bytes as f64);
+    println!("  impacted_files:  {} for {} files", mib(index_bytes), index.len());
+}
@@ -0,0 +1,40 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use kai::cache::ReportCache;
+use kai::parallel::load_reports;
+use kai::yaml_parser::parse_yaml;
+
+const COOLSTORE: &str = "samples/coolstore_analysis_output.yaml";
+
+fn parsing(c: &mut Criterion) {
+    let paths = vec![COOLSTORE.to_string(); 8];
+    let mut group = c.benchmark_group("load 8 coolstore reports");
+    group.sample_size(10);
+    group.bench_function("sequential", |b| {
+        b.iter(|| paths.iter().map(|path| parse_yaml(path).unwrap()).collect::<Vec<_>>())
+    });
+    group.bench_function("parallel", |b| b.iter(|| load_reports(&paths).unwrap()));
+    group.finish();
+}
+
+fn impacted_files(c: &mut Criterion) {
+    let report = parse_yaml(COOLSTORE).unwrap();
+    let mut group = c.benchmark_group("coolstore impacted files");
+    group.bench_function("sequential", |b| b.iter(|| report.impacted_files()));
+    group.bench_function("parallel", |b| b.iter(|| report.par_impacted_files()));
+    group.finish();
+}
+
+fn cache(c: &mut Criterion) {
+    let dir = std::env::temp_dir().join(format!("kai-bench-cache-{}", std::process::id()));
+    let cache = ReportCache::in_dir(&dir);
+    cache.load(COOLSTORE).unwrap();
+    let mut group = c.benchmark_group("load coolstore report");
+    group.sample_size(20);
+    group.bench_function("parse yaml", |b| b.iter(|| parse_yaml(COOLSTORE).unwrap()));
+    group.bench_function("binary cache", |b| b.iter(|| cache.load(COOLSTORE).unwrap()));
+    group.finish();
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+criterion_group!(benches, parsing, impacted_files, cache);
+criterion_main!(benches);
@@ -0,0 +1,15 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use kai::yaml_parser::AnalysisReport;
+
+// `load_from_file` opens the file and hands it to `AnalysisReport::from_reader`,
+// which is fuzzed directly to avoid a file per input. It covers the gzip and
+// zstd decompression and the YAML or JSON detection. Malformed input must come
+// back as an error, never as a panic.
+fuzz_target!(|data: &[u8]| {
+    if let Ok(report) = AnalysisReport::from_reader(data) {
+        let _ = report.impacted_files();
+        let _ = report.summary();
+    }
+});
@@ -0,0 +1,258 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+use serde::Serialize;
+use crate::storage::ReportStore;
+use crate::yaml_parser::AnalysisReport;
+
+/// Remaining effort and incidents of an application at one point in time.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct BurndownPoint {
+    pub at: String,
+    pub incidents: usize,
+    pub effort: i64,
+    pub mandatory_incidents: usize,
+    pub mandatory_effort: i64,
+}
+
+/// Remaining effort of one ruleset and category at one point in time.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct BurndownRow {
+    pub at: String,
+    pub ruleset: String,
+    pub category: String,
+    pub incidents: usize,
+    pub effort: i64,
+}
+
+/// Effort burndown over a sequence of analyses of the same application.
+///
+/// Points are kept sorted by their timestamp, compared as strings, so
+/// timestamps should be ISO 8601 dates or date-times.
+#[derive(Clone, Debug, Default)]
+pub struct Burndown {
+    points: Vec<BurndownPoint>,
+    // (at, ruleset, category) -> (incidents, effort)
+    breakdown: BTreeMap<(String, String, String), (usize, i64)>,
+}
+
+impl Burndown {
+
+    /// Builds the burndown of every imported run of `application`, each run
+    /// timestamped with its analysis time, or its import time when it was
+    /// imported without one. Of runs sharing a timestamp, the last one wins.
+    pub fn from_store(store: &ReportStore, application: &str) -> Result<Burndown, Box<dyn std::error::Error>> {
+        let mut burndown = Burndown::default();
+        for run in store.runs(application)? {
+            let at = run.analyzed_at.unwrap_or_else(|| format_unix_time(run.imported_at));
+            burndown.add(&at, &store.load_report(run.id)?);
+        }
+        Ok(burndown)
+    }
+
+    /// Adds the analysis taken at `at`, replacing the one added before at
+    /// the same time, if any.
+    pub fn add(&mut self, at: &str, report: &AnalysisReport) {
+        self.breakdown.retain(|(point_at, _, _), _| point_at != at);
+        self.points.retain(|point| point.at != at);
+        let mut point = BurndownPoint { at: at.to_string(), ..Default::default() };
+        for ruleset in &report.rulesets {
+            for violation in ruleset.violations.values() {
+                let incidents = violation.incidents.len();
+                let effort = violation.effort.unwrap_or(0) as i64 * incidents as i64;
+                let category = violation.category.clone().unwrap_or_else(|| "none".to_string());
+                point.incidents += incidents;
+                point.effort += effort;
+                if category == "mandatory" {
+                    point.mandatory_incidents += incidents;
+                    point.mandatory_effort += effort;
+                }
+                let row = self.breakdown.entry((at.to_string(), ruleset.name.to_string(), category)).or_default();
+                row.0 += incidents;
+                row.1 += effort;
+            }
+        }
+        let index = self.points.partition_point(|p| p.at < point.at);
+        self.points.insert(index, point);
+    }
+
+    /// Totals per point in time, oldest first.
+    pub fn points(&self) -> &[BurndownPoint] {
+        &self.points
+    }
+
+    /// Totals per point in time, ruleset and category.
+    ///
+    /// Every ruleset and category seen at any point has a row at every
+    /// point, so work that was burnt down shows as zero instead of vanishing.
+    pub fn breakdown(&self) -> Vec<BurndownRow> {
+        let groups: BTreeSet<(&str, &str)> = self.breakdown.keys()
+            .map(|(_, ruleset, category)| (ruleset.as_str(), category.as_str()))
+            .collect();
+        let mut rows = Vec::new();
+        for point in &self.points {
+            for (ruleset, category) in &groups {
+                let key = (point.at.clone(), ruleset.to_string(), category.to_string());
+                let (incidents, effort) = self.breakdown.get(&key).copied().unwrap_or_default();
+                rows.push(BurndownRow {
+                    at: point.at.clone(),
+                    ruleset: ruleset.to_string(),
+                    category: category.to_string(),
+                    incidents,
+                    effort,
+                });
+            }
+        }
+        rows
+    }
+
+    /// Horizontal bar chart of the remaining effort, `width` characters at
+    /// the widest. Mandatory effort is drawn with `#`, the rest with `=`.
+    pub fn ascii(&self, width: usize) -> String {
+        let max = self.points.iter().map(|p| p.effort).max().unwrap_or(0).max(1);
+        let label_width = self.points.iter().map(|p| p.at.len()).max().unwrap_or(0);
+        let scale = |effort: i64| (effort.max(0) as f64 * width as f64 / max as f64).round() as usize;
+        let mut out = String::new();
+        for point in &self.points {
+            let mandatory = scale(point.mandatory_effort);
+            let rest = scale(point.effort).saturating_sub(mandatory);
+            let _ = writeln!(out, "{:<label_width$}  {}{}{} {} (mandatory {})",
+                point.at,
+                "#".repeat(mandatory),
+                "=".repeat(rest),
+                " ".repeat(width.saturating_sub(mandatory + rest)),
+                point.effort,
+                point.mandatory_effort,
+                label_width = label_width);
+        }
+        out
+    }
+
+    /// Line chart of the total and mandatory effort as a standalone SVG document.
+    pub fn svg(&self, width: u32, height: u32) -> String {
+        const MARGIN: f64 = 40.0;
+        let (w, h) = (width as f64, height as f64);
+        let max = self.points.iter().map(|p| p.effort).max().unwrap_or(0).max(1) as f64;
+        let step = (w - 2.0 * MARGIN) / (self.points.len().max(2) - 1) as f64;
+        let x = |i: usize| MARGIN + i as f64 * step;
+        let y = |effort: i64| h - MARGIN - effort as f64 * (h - 2.0 * MARGIN) / max;
+        let polyline = |effort: fn(&BurndownPoint) -> i64| {
+            self.points.iter().enumerate()
+                .map(|(i, p)| format!("{:.1},{:.1}", x(i), y(effort(p))))
+                .collect::<Vec<_>>()
+                .join(" ")
+        };
+
+        let mut out = String::new();
+        let _ = writeln!(out, r#"<svg xmlns="http://www.w3.org/2000/svg" width="{}" height="{}" viewBox="0 0 {} {}" font-family="sans-serif" font-size="11">"#,
+            width, height, width, height);
+        let _ = writeln!(out, r#"  <line x1="{m}" y1="{b:.1}" x2="{r:.1}" y2="{b:.1}" stroke="black"/>"#, m = MARGIN, b = h - MARGIN, r = w - MARGIN);
+        let _ = writeln!(out, r#"  <line x1="{m}" y1="{m}" x2="{m}" y2="{b:.1}" stroke="black"/>"#, m = MARGIN, b = h - MARGIN);
+        let _ = writeln!(out, r#"  <text x="{:.1}" y="{:.1}" text-anchor="end">{}</text>"#, MARGIN - 4.0, MARGIN + 4.0, max);
+        let _ = writeln!(out, r#"  <text x="{:.1}" y="{:.1}" text-anchor="end">0</text>"#, MARGIN - 4.0, h - MARGIN + 4.0);
+        for (i, point) in self.points.iter().enumerate() {
+            let _ = writeln!(out, r#"  <text x="{:.1}" y="{:.1}" text-anchor="middle">{}</text>"#, x(i), h - MARGIN + 16.0, escape(&point.at));
+        }
+        let _ = writeln!(out, r#"  <polyline fill="none" stroke="steelblue" stroke-width="2" points="{}"><title>effort</title></polyline>"#,
+            polyline(|p| p.effort));
+        let _ = writeln!(out, r#"  <polyline fill="none" stroke="firebrick" stroke-width="2" points="{}"><title>mandatory effort</title></polyline>"#,
+            polyline(|p| p.mandatory_effort));
+        out.push_str("</svg>\n");
+        out
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+/// Formats seconds since the Unix epoch as an ISO 8601 UTC date-time.
+pub fn format_unix_time(seconds: i64) -> String {
+    let (days, time) = (seconds.div_euclid(86_400), seconds.rem_euclid(86_400));
+    // Civil date from days since 1970-01-01, after Howard Hinnant's `civil_from_days`.
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    format!("{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z", year, month, day, time / 3600, time % 3600 / 60, time % 60)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::yaml_parser::parse_yaml;
+
+    #[test]
+    fn burndown_of_a_shrinking_report() {
+        let report = parse_yaml("samples/demo-output.yaml").unwrap();
+        let mut fixed = report.clone();
+        for ruleset in &mut fixed.rulesets {
+            ruleset.violations.retain(|_, violation| violation.category.as_deref() != Some("mandatory"));
+        }
+
+        let mut burndown = Burndown::default();
+        burndown.add("2024-06-01", &fixed);
+        burndown.add("2024-05-01", &report);
+        let points = burndown.points();
+        assert_eq!(points.iter().map(|p| p.at.as_str()).collect::<Vec<_>>(), vec!["2024-05-01", "2024-06-01"]);
+        assert_eq!((points[0].incidents, points[0].effort, points[0].mandatory_incidents), (86, 100, 4));
+        assert_eq!((points[1].mandatory_incidents, points[1].mandatory_effort), (0, 0));
+        assert_eq!(points[1].effort, points[0].effort - points[0].mandatory_effort);
+
+        // Burnt down groups stay in the breakdown with zero effort.
+        let rows = burndown.breakdown();
+        assert_eq!(rows.len() % 2, 0);
+        let mandatory: Vec<_> = rows.iter().filter(|r| r.category == "mandatory").collect();
+        assert_eq!(mandatory.iter().map(|r| r.incidents).sum::<usize>(), 4);
+        assert!(mandatory.iter().any(|r| r.at == "2024-06-01" && r.incidents == 0));
+
+        let ascii = burndown.ascii(20);
+        assert_eq!(ascii.lines().next().unwrap(), "2024-05-01  ##================== 100 (mandatory 12)");
+        let svg = burndown.svg(640, 320);
+        assert!(svg.starts_with("<svg") && svg.trim_end().ends_with("</svg>"));
+        assert_eq!(svg.matches("<polyline").count(), 2);
+    }
+
+    #[test]
+    fn burndown_from_store() {
+        let report = parse_yaml("samples/demo-output.yaml").unwrap();
+        let mut store = ReportStore::open_in_memory().unwrap();
+        store.import_report_at("demo", "second.yaml", "2024-06-01", &report.filter_by_label("testing")).unwrap();
+        store.import_report_at("demo", "first.yaml", "2024-05-01", &report).unwrap();
+
+        let burndown = Burndown::from_store(&store, "demo").unwrap();
+        let points = burndown.points();
+        assert_eq!(points.iter().map(|p| p.at.as_str()).collect::<Vec<_>>(), vec!["2024-05-01", "2024-06-01"]);
+        assert_eq!(points[0].incidents, 86);
+        assert!(points[1].incidents < points[0].incidents);
+    }
+
+    #[test]
+    fn same_timestamp_replaces_the_point() {
+        let report = parse_yaml("samples/demo-output.yaml").unwrap();
+        let testing = report.filter_by_label("testing");
+        let mut burndown = Burndown::default();
+        burndown.add("2024-06-01T10:00:00Z", &report);
+        burndown.add("2024-06-01T10:00:00Z", &testing);
+
+        let expected = {
+            let mut burndown = Burndown::default();
+            burndown.add("2024-06-01T10:00:00Z", &testing);
+            burndown
+        };
+        assert_eq!(burndown.points(), expected.points());
+        assert_eq!(burndown.breakdown(), expected.breakdown());
+        assert_eq!(burndown.breakdown().iter().map(|r| r.incidents).sum::<usize>(), burndown.points()[0].incidents);
+    }
+
+    #[test]
+    fn unix_time_as_iso_8601() {
+        assert_eq!(format_unix_time(0), "1970-01-01T00:00:00Z");
+        assert_eq!(format_unix_time(951_782_400), "2000-02-29T00:00:00Z");
+        assert_eq!(format_unix_time(1_717_243_199), "2024-06-01T11:59:59Z");
+    }
+}
@@ -0,0 +1,172 @@
+use std::fmt::Write;
+use std::fs;
+use std::path::{Path, PathBuf};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use crate::yaml_parser::AnalysisReport;
+
+// Bumped whenever the cached layout changes, together with the crate version
+// it makes old cache files miss instead of failing to decode.
+const CACHE_FORMAT: u32 = 1;
+
+const EXTENSION: &str = "kai-cache";
+
+/// On-disk envelope of a cached report.
+#[derive(Debug, Deserialize, Serialize)]
+struct CacheEntry {
+    format: u32,
+    kai_version: String,
+    source_hash: String,
+    report: AnalysisReport,
+}
+
+/// Whether `ReportCache::load` could use the cache.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CacheStatus {
+    Hit,
+    Miss,
+}
+
+/// Binary cache of parsed reports, keyed by the SHA-256 of the source file.
+///
+/// Reports are stored as MessagePack. bincode was the first choice, but it
+/// cannot decode the optional fields and the `serde_json::Value` incident
+/// variables, which need a self-describing format.
+#[derive(Clone, Debug, Default)]
+pub struct ReportCache {
+    dir: Option<PathBuf>,
+}
+
+impl ReportCache {
+
+    /// Caches each report in a `<report>.kai-cache` file next to it.
+    pub fn beside_source() -> ReportCache {
+        ReportCache { dir: None }
+    }
+
+    /// Caches reports in `dir` as `<hash>.kai-cache`, so identical reports share an entry.
+    pub fn in_dir(dir: impl Into<PathBuf>) -> ReportCache {
+        ReportCache { dir: Some(dir.into()) }
+    }
+
+    fn entry_path(&self, source: &Path, hash: &str) -> PathBuf {
+        match &self.dir {
+            Some(dir) => dir.join(format!("{}.{}", hash, EXTENSION)),
+            None => {
+                let mut name = source.as_os_str().to_owned();
+                name.push(format!(".{}", EXTENSION));
+                PathBuf::from(name)
+            }
+        }
+    }
+
+    /// Loads the report at `path`, from the cache when the source has not changed.
+    ///
+    /// The source is always read to compute its hash, but only parsed on a
+    /// miss, after which the cache is refreshed. Failing to write the cache
+    /// is not an error, the report is returned all the same. Stdin (`-`) is
+    /// never cached.
+    pub fn load(&self, path: &str) -> Result<(AnalysisReport, CacheStatus), Box<dyn std::error::Error>> {
+        if path == "-" {
+            return Ok((AnalysisReport::open(path)?, CacheStatus::Miss));
+        }
+        let source = fs::read(path)?;
+        let hash = source_hash(&source);
+        let entry_path = self.entry_path(Path::new(path), &hash);
+
+        if let Some(report) = read_entry(&entry_path, &hash) {
+            return Ok((report, CacheStatus::Hit));
+        }
+        let report = AnalysisReport::from_reader(source.as_slice())?;
+        let entry = CacheEntry {
+            format: CACHE_FORMAT,
+            kai_version: env!("CARGO_PKG_VERSION").to_string(),
+            source_hash: hash,
+            report,
+        };
+        if let Err(e) = write_entry(&entry_path, &entry) {
+            eprintln!("{}: could not write cache: {}", entry_path.display(), e);
+        }
+        Ok((entry.report, CacheStatus::Miss))
+    }
+}
+
+/// Hex encoded SHA-256 of `bytes`.
+pub fn source_hash(bytes: &[u8]) -> String {
+    let mut hex = String::with_capacity(64);
+    for byte in Sha256::digest(bytes) {
+        let _ = write!(hex, "{:02x}", byte);
+    }
+    hex
+}
+
+// Any unreadable, outdated or foreign entry is treated as a miss.
+fn read_entry(path: &Path, hash: &str) -> Option<AnalysisReport> {
+    let bytes = fs::read(path).ok()?;
+    let entry: CacheEntry = rmp_serde::from_slice(&bytes).ok()?;
+    let current = entry.format == CACHE_FORMAT
+        && entry.kai_version == env!("CARGO_PKG_VERSION")
+        && entry.source_hash == hash;
+    current.then(|| {
+        let mut report = entry.report;
+        report.intern();
+        report
+    })
+}
+
+fn write_entry(path: &Path, entry: &CacheEntry) -> Result<(), Box<dyn std::error::Error>> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    // Write then rename so a concurrent reader never sees half an entry.
+    let partial = path.with_extension(format!("{}.{}", EXTENSION, std::process::id()));
+    fs::write(&partial, rmp_serde::to_vec_named(entry)?)?;
+    fs::rename(&partial, path)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::yaml_parser::parse_yaml;
+
+    #[test]
+    fn cache_hits_until_the_source_changes() {
+        let dir = std::env::temp_dir().join(format!("kai-cache-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let source = dir.join("output.yaml");
+        fs::copy("samples/demo-output.yaml", &source).unwrap();
+        let source = source.to_str().unwrap();
+        let cache = ReportCache::beside_source();
+        let expected = parse_yaml(source).unwrap();
+
+        assert_eq!(cache.load(source).unwrap(), (expected.clone(), CacheStatus::Miss));
+        assert!(dir.join("output.yaml.kai-cache").is_file());
+        assert_eq!(cache.load(source).unwrap(), (expected, CacheStatus::Hit));
+
+        let mut yaml = fs::read_to_string(source).unwrap();
+        yaml = yaml.replacen("lineNumber: 45", "lineNumber: 46", 1);
+        fs::write(source, yaml).unwrap();
+        let (report, status) = cache.load(source).unwrap();
+        assert_eq!(status, CacheStatus::Miss);
+        assert_eq!(report, parse_yaml(source).unwrap());
+
+        // A corrupt entry is a miss, and gets replaced.
+        fs::write(dir.join("output.yaml.kai-cache"), b"garbage").unwrap();
+        assert_eq!(cache.load(source).unwrap().1, CacheStatus::Miss);
+        assert_eq!(cache.load(source).unwrap().1, CacheStatus::Hit);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn cache_dir_is_keyed_by_hash() {
+        let dir = std::env::temp_dir().join(format!("kai-cache-dir-{}", std::process::id()));
+        let cache = ReportCache::in_dir(&dir);
+        let (_, status) = cache.load("samples/coolstore_analysis_output.yaml").unwrap();
+        assert_eq!(status, CacheStatus::Miss);
+        let hash = source_hash(&fs::read("samples/coolstore_analysis_output.yaml").unwrap());
+        assert!(dir.join(format!("{}.kai-cache", hash)).is_file());
+        assert_eq!(cache.load("samples/coolstore_analysis_output.yaml").unwrap().1, CacheStatus::Hit);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,120 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::Arc;
+use serde::{Deserialize, Serialize};
+use crate::yaml_parser::{AnalysisReport, Link};
+
+/// Metadata of one rule, merged from every report it appeared in.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct CatalogRule {
+    pub rule_id: Arc<str>,
+    pub ruleset: Arc<str>,
+    pub description: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub category: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub effort: Option<i32>,
+    pub labels: BTreeSet<Arc<str>>,
+    pub links: Vec<Link>,
+    /// Applications with at least one incident of this rule.
+    pub applications: BTreeSet<String>,
+    /// Incidents across all applications.
+    pub incidents: usize,
+}
+
+/// De-duplicated catalog of the rules hit across many reports, keyed by rule ID.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct RuleCatalog {
+    pub rules: BTreeMap<Arc<str>, CatalogRule>,
+}
+
+impl RuleCatalog {
+
+    /// Builds a catalog from `(application, report)` pairs.
+    pub fn from_reports<'a>(reports: impl IntoIterator<Item = (&'a str, &'a AnalysisReport)>) -> RuleCatalog {
+        let mut catalog = RuleCatalog::default();
+        for (application, report) in reports {
+            catalog.add_report(application, report);
+        }
+        catalog
+    }
+
+    /// Adds the violations of `report` to the catalog.
+    ///
+    /// Metadata comes from the first report a rule was seen in, labels and
+    /// links are merged. Violations without incidents do not count as a hit.
+    pub fn add_report(&mut self, application: &str, report: &AnalysisReport) {
+        for ruleset in &report.rulesets {
+            for (rule_id, violation) in &ruleset.violations {
+                let rule = self.rules.entry(rule_id.clone()).or_insert_with(|| CatalogRule {
+                    rule_id: rule_id.clone(),
+                    ruleset: ruleset.name.clone(),
+                    description: violation.description.clone(),
+                    category: violation.category.clone(),
+                    effort: violation.effort,
+                    ..Default::default()
+                });
+                rule.labels.extend(violation.labels.iter().cloned());
+                for link in &violation.links {
+                    if !rule.links.contains(link) {
+                        rule.links.push(link.clone());
+                    }
+                }
+                if !violation.incidents.is_empty() {
+                    rule.applications.insert(application.to_string());
+                    rule.incidents += violation.incidents.len();
+                }
+            }
+        }
+    }
+
+    pub fn get(&self, rule_id: &str) -> Option<&CatalogRule> {
+        self.rules.get(rule_id)
+    }
+
+    /// Rules ordered by the number of applications hitting them, then by
+    /// incident count and rule ID.
+    pub fn most_common(&self) -> Vec<&CatalogRule> {
+        let mut rules: Vec<&CatalogRule> = self.rules.values().collect();
+        rules.sort_by(|a, b| {
+            b.applications.len().cmp(&a.applications.len())
+                .then(b.incidents.cmp(&a.incidents))
+                .then(a.rule_id.cmp(&b.rule_id))
+        });
+        rules
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::yaml_parser::parse_yaml;
+
+    #[test]
+    fn catalog_across_reports() {
+        let coolstore = parse_yaml("samples/coolstore_analysis_output.yaml").unwrap();
+        let demo = parse_yaml("samples/demo-output.yaml").unwrap();
+        let catalog = RuleCatalog::from_reports([
+            ("coolstore", &coolstore),
+            ("coolstore-copy", &coolstore),
+            ("demo", &demo),
+        ]);
+
+        let counts = coolstore.violation_counts();
+        let rule = catalog.get("java-rpc-00000").unwrap();
+        assert_eq!(rule.description, "Java API for XML-based RPC (JAX-RPC)");
+        assert_eq!(rule.applications.iter().collect::<Vec<_>>(), vec!["coolstore", "coolstore-copy"]);
+        assert_eq!(rule.incidents, 2 * counts["java-rpc-00000"]);
+        assert_eq!(rule.links.len(), 1);
+
+        let demo_rule = catalog.get("chain-pom-001").unwrap();
+        assert_eq!(demo_rule.applications.len(), 1);
+
+        let most_common = catalog.most_common();
+        assert_eq!(most_common.len(), catalog.rules.len());
+        assert!(most_common.windows(2).all(|w| w[0].applications.len() >= w[1].applications.len()));
+        assert_eq!(&*most_common[0].rule_id, "maven-javax-to-jakarta-00002");
+        assert_eq!(most_common[0].applications.len(), 3);
+    }
+}
@@ -0,0 +1,727 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use glob::{MatchOptions, Pattern};
+use serde::{Deserialize, Serialize};
+use crate::cache::ReportCache;
+use crate::location::{local_path, Editor, LocationOptions};
+use crate::prompt::{ChatMessage, ChatRequest, PromptOptions};
+use crate::selector::MigrationPath;
+use crate::suppression::{SuppressionResult, Suppressions};
+use crate::yaml_parser::AnalysisReport;
+
+/// Name of the project configuration file, looked up from the working
+/// directory upwards.
+pub const PROJECT_FILE: &str = "kai.toml";
+
+/// Output format used by commands that can print more than one.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputFormat {
+    #[default]
+    Text,
+    Json,
+    Csv,
+    Tsv,
+}
+
+impl OutputFormat {
+    pub fn parse(name: &str) -> Option<OutputFormat> {
+        match name {
+            "text" => Some(OutputFormat::Text),
+            "json" => Some(OutputFormat::Json),
+            "csv" => Some(OutputFormat::Csv),
+            "tsv" => Some(OutputFormat::Tsv),
+            _ => None,
+        }
+    }
+}
+
+/// Globs selecting the incidents kept from a report, matched against the
+/// incident URI or its path like suppressions are.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Filters {
+    /// Incidents must match one of these, when any is given.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub include: Vec<String>,
+    /// Incidents matching any of these are dropped.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub exclude: Vec<String>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct OutputSettings {
+    pub format: OutputFormat,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub editor: Option<String>,
+}
+
+/// Services serving the chat completions API that fix prompts are sent to.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LlmProvider {
+    #[default]
+    OpenAi,
+    Ollama,
+}
+
+impl LlmProvider {
+    pub fn parse(name: &str) -> Option<LlmProvider> {
+        match name {
+            "openai" => Some(LlmProvider::OpenAi),
+            "ollama" => Some(LlmProvider::Ollama),
+            _ => None,
+        }
+    }
+
+    /// Chat completions URL of the provider, used when no endpoint is configured.
+    pub fn default_endpoint(&self) -> &'static str {
+        match self {
+            LlmProvider::OpenAi => "https://api.openai.com/v1/chat/completions",
+            LlmProvider::Ollama => "http://localhost:11434/v1/chat/completions",
+        }
+    }
+}
+
+/// Settings of the model that fix prompts are sent to, see `Config::chat_request`.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LlmSettings {
+    pub provider: LlmProvider,
+    /// Chat completions URL, the default one of the provider when unset.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub endpoint: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_tokens: Option<u32>,
+    /// Past fixes included in prompts, see `PromptOptions`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub examples: Option<usize>,
+    /// Snippet lines around the incident included in prompts.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub context: Option<usize>,
+}
+
+/// Settings shared by the CLI and the library entry points.
+///
+/// Layers are read in order, later ones overriding earlier ones key by key:
+/// the user file (`$XDG_CONFIG_HOME/kai/config.toml`), the project file
+/// (`$KAI_CONFIG`, or the nearest `kai.toml`), then `KAI_*` environment
+/// variables, where an empty value clears the setting.
+///
+/// Relative local paths in a file layer, the reports, `cache_dir`,
+/// `fixes_db`, `suppressions` and the `uri_prefixes` directories, are
+/// relative to that file. Paths in `KAI_*` variables are used as given.
+///
+/// ```toml
+/// reports = ["coolstore=output/output.yaml"]
+/// source_root = "/opt/input/source"
+/// suppressions = "suppressions.yaml"
+/// sources = ["java-ee"]
+/// targets = ["quarkus"]
+///
+/// [uri_prefixes]
+/// "file:///opt/input/source" = "/home/me/src/coolstore"
+///
+/// [filters]
+/// exclude = ["file:///root/.m2/**"]
+///
+/// [output]
+/// format = "json"
+/// editor = "vscode"
+///
+/// [llm]
+/// provider = "ollama"
+/// endpoint = "http://gpu-box:11434/v1/chat/completions"
+/// model = "llama3.1"
+/// temperature = 0.2
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Reports used by commands given none, as `[name=]path`.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub reports: Vec<String>,
+    /// Root of the analyzed sources, paths are shown relative to it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source_root: Option<String>,
+    /// Binary cache of parsed reports, see `ReportCache::in_dir`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cache_dir: Option<String>,
+    /// Knowledge base of past fixes, see `FixStore`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fixes_db: Option<String>,
+    /// Suppression file applied by `load_report`, see `Suppressions`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub suppressions: Option<String>,
+    /// Only violations carrying all of these labels are kept.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub labels: Vec<String>,
+    /// Migration path the reports are narrowed to, see `MigrationPath`.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub sources: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub targets: Vec<String>,
+    /// Analyzer URI prefixes and the local directories they map to.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub uri_prefixes: BTreeMap<String, String>,
+    pub filters: Filters,
+    pub output: OutputSettings,
+    pub llm: LlmSettings,
+}
+
+type Env<'a> = &'a dyn Fn(&str) -> Option<String>;
+
+impl Config {
+    /// Reads every layer for the current user, directory and environment.
+    pub fn load() -> Result<Config, Box<dyn std::error::Error>> {
+        let env = |name: &str| std::env::var(name).ok();
+        Config::from_layers(&Config::layer_paths(&env, &std::env::current_dir()?), &env)
+    }
+
+    /// The configuration files that exist, lowest precedence first.
+    pub fn layer_paths(env: Env, dir: &Path) -> Vec<PathBuf> {
+        let user_dir = env("XDG_CONFIG_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| env("HOME").map(|home| Path::new(&home).join(".config")));
+        let user = user_dir.map(|dir| dir.join("kai").join("config.toml"));
+        let project = match env("KAI_CONFIG").filter(|path| !path.is_empty()) {
+            // Named explicitly, so a missing file is reported rather than skipped.
+            Some(path) => return user.into_iter().filter(|p| p.is_file()).chain([PathBuf::from(path)]).collect(),
+            None => dir.ancestors().map(|dir| dir.join(PROJECT_FILE)).find(|path| path.is_file()),
+        };
+        user.into_iter().chain(project).filter(|path| path.is_file()).collect()
+    }
+
+    /// Merges `files` in order, then applies the `KAI_*` variables of `env`.
+    ///
+    /// Every problem found is reported, one per line, prefixed with the file
+    /// or variable it comes from.
+    pub fn from_layers(files: &[PathBuf], env: Env) -> Result<Config, Box<dyn std::error::Error>> {
+        let mut merged = toml::Table::new();
+        let mut errors = Vec::new();
+        for path in files {
+            match read_layer(path) {
+                Ok(table) => merge(&mut merged, table),
+                Err(e) => errors.extend(e.to_string().lines().map(|line| format!("{}: {}", path.display(), line))),
+            }
+        }
+        let mut config: Config = merged.try_into()?;
+        config.apply_env(env, &mut errors);
+        if let Err(e) = config.validate() {
+            errors.push(e.to_string());
+        }
+        if !errors.is_empty() {
+            return Err(errors.join("\n").into());
+        }
+        Ok(config)
+    }
+
+    /// Parses and validates a single configuration file.
+    pub fn parse(contents: &str) -> Result<Config, Box<dyn std::error::Error>> {
+        let config: Config = toml::from_str(contents)?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    // Set but empty variables clear the setting: optional values are unset,
+    // lists emptied and the output format and provider reset to their defaults.
+    fn apply_env(&mut self, env: Env, errors: &mut Vec<String>) {
+        let list = |value: String| value.split(',').map(|item| item.trim().to_string()).filter(|item| !item.is_empty()).collect();
+        if let Some(reports) = env("KAI_REPORTS") {
+            self.reports = list(reports);
+        }
+        if let Some(labels) = env("KAI_LABELS") {
+            self.labels = list(labels);
+        }
+        if let Some(sources) = env("KAI_SOURCES") {
+            self.sources = list(sources);
+        }
+        if let Some(targets) = env("KAI_TARGETS") {
+            self.targets = list(targets);
+        }
+        for (name, field) in [
+            ("KAI_SOURCE_ROOT", &mut self.source_root),
+            ("KAI_CACHE_DIR", &mut self.cache_dir),
+            ("KAI_FIXES_DB", &mut self.fixes_db),
+            ("KAI_SUPPRESSIONS", &mut self.suppressions),
+            ("KAI_EDITOR", &mut self.output.editor),
+            ("KAI_LLM_ENDPOINT", &mut self.llm.endpoint),
+            ("KAI_LLM_MODEL", &mut self.llm.model),
+        ] {
+            if let Some(value) = env(name) {
+                *field = Some(value).filter(|value| !value.is_empty());
+            }
+        }
+        if let Some(temperature) = env("KAI_LLM_TEMPERATURE") {
+            match temperature.parse() {
+                _ if temperature.is_empty() => self.llm.temperature = None,
+                Ok(temperature) => self.llm.temperature = Some(temperature),
+                Err(_) => errors.push(format!("KAI_LLM_TEMPERATURE: expected a number, not `{}`", temperature)),
+            }
+        }
+        if let Some(max_tokens) = env("KAI_LLM_MAX_TOKENS") {
+            match max_tokens.parse() {
+                _ if max_tokens.is_empty() => self.llm.max_tokens = None,
+                Ok(max_tokens) => self.llm.max_tokens = Some(max_tokens),
+                Err(_) => errors.push(format!("KAI_LLM_MAX_TOKENS: expected a positive integer, not `{}`", max_tokens)),
+            }
+        }
+        if let Some(format) = env("KAI_OUTPUT_FORMAT") {
+            match OutputFormat::parse(&format) {
+                _ if format.is_empty() => self.output.format = OutputFormat::default(),
+                Some(format) => self.output.format = format,
+                None => errors.push(format!("KAI_OUTPUT_FORMAT: unknown format {}, expected text, json, csv or tsv", format)),
+            }
+        }
+        if let Some(provider) = env("KAI_LLM_PROVIDER") {
+            match LlmProvider::parse(&provider) {
+                _ if provider.is_empty() => self.llm.provider = LlmProvider::default(),
+                Some(provider) => self.llm.provider = provider,
+                None => errors.push(format!("KAI_LLM_PROVIDER: unknown provider {}, expected openai or ollama", provider)),
+            }
+        }
+    }
+
+    /// Checks the values that parse but make no sense.
+    pub fn validate(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let mut errors = Vec::new();
+        if self.reports.iter().any(|report| report.trim().is_empty()) {
+            errors.push("reports: empty report path".to_string());
+        }
+        if self.labels.iter().any(|label| label.trim().is_empty()) {
+            errors.push("labels: empty label".to_string());
+        }
+        for (key, choices) in [("sources", &self.sources), ("targets", &self.targets)] {
+            if choices.iter().any(|choice| choice.trim().is_empty() || choice.contains('=')) {
+                errors.push(format!("{}: expected technologies such as `java-ee` or `eap8`, not labels", key));
+            }
+        }
+        for (prefix, dir) in &self.uri_prefixes {
+            if prefix.is_empty() || dir.is_empty() {
+                errors.push(format!("uri_prefixes: `{}` = `{}` maps an empty prefix or directory", prefix, dir));
+            }
+        }
+        for (key, globs) in [("include", &self.filters.include), ("exclude", &self.filters.exclude)] {
+            for glob in globs {
+                if let Err(e) = Pattern::new(glob) {
+                    errors.push(format!("filters.{}: invalid glob `{}`: {}", key, glob, e));
+                }
+            }
+        }
+        if let Some(editor) = &self.output.editor {
+            if Editor::parse(editor).is_none() {
+                errors.push(format!("output.editor: unknown editor {}, expected vscode or idea", editor));
+            }
+        }
+        let llm = &self.llm;
+        if let Some(endpoint) = &llm.endpoint {
+            if !endpoint.starts_with("http://") && !endpoint.starts_with("https://") {
+                errors.push(format!("llm.endpoint: expected an http or https URL, not `{}`", endpoint));
+            }
+        }
+        if llm.model.as_deref().is_some_and(|model| model.trim().is_empty()) {
+            errors.push("llm.model: empty model".to_string());
+        }
+        if let Some(temperature) = llm.temperature {
+            if !(0.0..=2.0).contains(&temperature) {
+                errors.push(format!("llm.temperature: {} is outside of 0 to 2", temperature));
+            }
+        }
+        if llm.max_tokens == Some(0) {
+            errors.push("llm.max_tokens: must be positive".to_string());
+        }
+        if !errors.is_empty() {
+            return Err(errors.join("\n").into());
+        }
+        Ok(())
+    }
+
+    /// The binary report cache, when `cache_dir` is set.
+    pub fn report_cache(&self) -> Option<ReportCache> {
+        self.cache_dir.as_ref().map(ReportCache::in_dir)
+    }
+
+    /// Reads the report at `path`, through the cache when one is configured.
+    pub fn open_report(&self, path: &str) -> Result<AnalysisReport, Box<dyn std::error::Error>> {
+        match self.report_cache() {
+            Some(cache) => Ok(cache.load(path)?.0),
+            None => AnalysisReport::open(path),
+        }
+    }
+
+    /// Same as `open_report`, narrowed with `select` and without the incidents
+    /// matched by the configured suppression file.
+    pub fn load_report(&self, path: &str) -> Result<AnalysisReport, Box<dyn std::error::Error>> {
+        let suppressions = self.load_suppressions()?;
+        Ok(self.load_suppressed(path, suppressions.as_ref())?.report)
+    }
+
+    /// Same as `load_report` with `suppressions` instead of the configured
+    /// ones, also returning the incidents they removed.
+    pub fn load_suppressed(&self, path: &str, suppressions: Option<&Suppressions>) -> Result<SuppressionResult, Box<dyn std::error::Error>> {
+        let report = self.select(&self.open_report(path)?)?;
+        Ok(match suppressions {
+            Some(suppressions) => suppressions.apply(&report, self.source_root.as_deref()),
+            None => SuppressionResult { report, ..Default::default() },
+        })
+    }
+
+    /// The configured suppression file, parsed and validated.
+    pub fn load_suppressions(&self) -> Result<Option<Suppressions>, Box<dyn std::error::Error>> {
+        self.suppressions
+            .as_deref()
+            .map(|path| self.load_suppression_file(path))
+            .transpose()
+    }
+
+    /// Parses and validates the suppression file at `path`, checking that
+    /// `source_root` is set when it has fingerprint entries.
+    pub fn load_suppression_file(&self, path: &str) -> Result<Suppressions, Box<dyn std::error::Error>> {
+        Suppressions::load(path)
+            .and_then(|suppressions| suppressions.check_source_root(self.source_root.as_deref()).map(|_| suppressions))
+            .map_err(|e| format!("{}: {}", path, e).into())
+    }
+
+    /// The configured sources and targets.
+    pub fn migration_path(&self) -> MigrationPath {
+        MigrationPath::new(self.sources.clone(), self.targets.clone())
+    }
+
+    /// Keeps the violations on the migration path carrying every configured
+    /// label, and the incidents passing the path filters. Insights off the
+    /// path are dropped too. Violations left without incidents are dropped,
+    /// rulesets are kept, with their `unmatched` rules and `errors`.
+    ///
+    /// Fails on an invalid `include` or `exclude` glob, which `validate`
+    /// reports for loaded settings but a `Config` built by hand can hold.
+    pub fn select(&self, report: &AnalysisReport) -> Result<AnalysisReport, Box<dyn std::error::Error>> {
+        let path = self.migration_path();
+        let mut selected = report.clone();
+        for ruleset in &mut selected.rulesets {
+            ruleset.violations.retain(|_, violation| {
+                path.selects(&violation.labels)
+                    && self.labels.iter().all(|label| violation.labels.iter().any(|l| **l == **label))
+            });
+            ruleset.insights.retain(|_, insight| path.selects(&insight.labels));
+        }
+        let compile = |key: &str, globs: &[String]| {
+            globs.iter()
+                .map(|glob| Pattern::new(glob).map_err(|e| format!("filters.{}: invalid glob `{}`: {}", key, glob, e)))
+                .collect::<Result<Vec<_>, String>>()
+        };
+        let (include, exclude) = (compile("include", &self.filters.include)?, compile("exclude", &self.filters.exclude)?);
+        if include.is_empty() && exclude.is_empty() {
+            return Ok(selected);
+        }
+        let options = MatchOptions { require_literal_separator: true, ..Default::default() };
+        let matches = |pattern: &Pattern, uri: &str| {
+            pattern.matches_with(uri, options) || pattern.matches_with(uri.strip_prefix("file://").unwrap_or(uri), options)
+        };
+        for ruleset in &mut selected.rulesets {
+            for violation in ruleset.violations.values_mut() {
+                violation.incidents.retain(|incident| {
+                    (include.is_empty() || include.iter().any(|p| matches(p, &incident.uri)))
+                        && !exclude.iter().any(|p| matches(p, &incident.uri))
+                });
+            }
+            ruleset.violations.retain(|_, violation| !violation.incidents.is_empty());
+        }
+        Ok(selected)
+    }
+
+    /// Local path of an analyzer `uri`, see `location::local_path`.
+    pub fn local_path(&self, uri: &str) -> Option<String> {
+        local_path(&self.uri_prefixes, uri)
+    }
+
+    /// Location options with the source root, the URI prefix mappings and the editor.
+    pub fn location_options(&self) -> LocationOptions {
+        LocationOptions {
+            root: self.source_root.clone(),
+            checkout: None,
+            uri_prefixes: self.uri_prefixes.clone(),
+            editor: self.output.editor.as_deref().and_then(Editor::parse),
+        }
+    }
+
+    pub fn prompt_options(&self) -> PromptOptions {
+        let defaults = PromptOptions::default();
+        PromptOptions {
+            examples: self.llm.examples.unwrap_or(defaults.examples),
+            context: self.llm.context.unwrap_or(defaults.context),
+            ..defaults
+        }
+    }
+
+    /// The URL chat requests are posted to.
+    pub fn chat_url(&self) -> &str {
+        self.llm.endpoint.as_deref().unwrap_or(self.llm.provider.default_endpoint())
+    }
+
+    /// The request sending `prompt` to the configured model, see `chat_url`.
+    pub fn chat_request(&self, prompt: String) -> Result<ChatRequest, Box<dyn std::error::Error>> {
+        let model = self.llm.model.clone().ok_or("llm.model: no model configured")?;
+        Ok(ChatRequest {
+            model,
+            messages: vec![ChatMessage { role: "user".to_string(), content: prompt }],
+            temperature: self.llm.temperature,
+            max_tokens: self.llm.max_tokens,
+        })
+    }
+}
+
+fn read_layer(path: &Path) -> Result<toml::Table, Box<dyn std::error::Error>> {
+    let contents = fs::read_to_string(path)?;
+    let mut table: toml::Table = toml::from_str(&contents)?;
+    // Types are checked on their own so that errors name the file they come
+    // from. Values are validated once merged, a layer may complete another.
+    table.clone().try_into::<Config>()?;
+    let dir = path.parent().unwrap_or(Path::new(""));
+    let resolve = |value: &mut toml::Value| {
+        if let toml::Value::String(path) = value {
+            *path = resolve_path(dir, path);
+        }
+    };
+    for key in ["cache_dir", "fixes_db", "suppressions"] {
+        table.get_mut(key).map(resolve);
+    }
+    if let Some(toml::Value::Table(prefixes)) = table.get_mut("uri_prefixes") {
+        prefixes.iter_mut().for_each(|(_, dir)| resolve(dir));
+    }
+    if let Some(toml::Value::Array(reports)) = table.get_mut("reports") {
+        for report in reports {
+            if let toml::Value::String(report) = report {
+                *report = match report.split_once('=') {
+                    Some((name, path)) => format!("{}={}", name, resolve_path(dir, path)),
+                    None => resolve_path(dir, report),
+                };
+            }
+        }
+    }
+    Ok(table)
+}
+
+fn resolve_path(dir: &Path, path: &str) -> String {
+    if path == "-" || Path::new(path).is_absolute() || dir.as_os_str().is_empty() {
+        path.to_string()
+    } else {
+        dir.join(path).to_string_lossy().to_string()
+    }
+}
+
+// Tables are merged key by key, any other value of `layer` replaces the one in `base`.
+fn merge(base: &mut toml::Table, layer: toml::Table) {
+    for (key, value) in layer {
+        match (base.get_mut(&key), value) {
+            (Some(toml::Value::Table(base)), toml::Value::Table(layer)) => merge(base, layer),
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::location::locations;
+    use crate::yaml_parser::parse_yaml;
+
+    #[test]
+    fn layers_and_environment() {
+        let dir = std::env::temp_dir().join(format!("kai-config-{}", std::process::id()));
+        let project = dir.join("app");
+        fs::create_dir_all(project.join("src")).unwrap();
+        fs::create_dir_all(dir.join("home/kai")).unwrap();
+        fs::write(dir.join("home/kai/config.toml"), "
+            fixes_db = \"fixes.db\"
+            [output]
+            format = \"json\"
+            editor = \"idea\"
+            [llm]
+            model = \"gpt-4o\"
+            temperature = 0.2
+        ").unwrap();
+        fs::write(project.join(PROJECT_FILE), "
+            reports = [\"coolstore=out/output.yaml\"]
+            source_root = \"/opt/input/source\"
+            suppressions = \"suppressions.yaml\"
+            [uri_prefixes]
+            \"file:///opt/input/source\" = \"src\"
+            [output]
+            editor = \"vscode\"
+            [llm]
+            provider = \"ollama\"
+            model = \"gpt-4o-mini\"
+        ").unwrap();
+
+        let vars: BTreeMap<&str, String> = [
+            ("XDG_CONFIG_HOME", dir.join("home").to_string_lossy().to_string()),
+            ("KAI_LABELS", "konveyor.io/target=quarkus, konveyor.io/source".to_string()),
+            ("KAI_TARGETS", "quarkus,eap8".to_string()),
+            ("KAI_CACHE_DIR", "".to_string()),
+            ("KAI_SOURCE_ROOT", "".to_string()),
+            ("KAI_LLM_TEMPERATURE", "0.7".to_string()),
+            ("KAI_LLM_MAX_TOKENS", "512".to_string()),
+            ("KAI_LLM_ENDPOINT", "http://gpu-box:11434/v1/chat/completions".to_string()),
+        ].into_iter().collect();
+        let env = |name: &str| vars.get(name).cloned();
+        let paths = Config::layer_paths(&env, &project.join("src"));
+        assert_eq!(paths, vec![dir.join("home/kai/config.toml"), project.join(PROJECT_FILE)]);
+
+        let config = Config::from_layers(&paths, &env).unwrap();
+        assert_eq!(config.reports, vec![format!("coolstore={}", project.join("out/output.yaml").display())]);
+        assert_eq!(config.fixes_db, Some(dir.join("home/kai/fixes.db").to_string_lossy().to_string()));
+        assert_eq!(config.suppressions, Some(project.join("suppressions.yaml").to_string_lossy().to_string()));
+        assert_eq!(config.output, OutputSettings { format: OutputFormat::Json, editor: Some("vscode".to_string()) });
+        assert_eq!(config.local_path("file:///opt/input/source/pom.xml"), Some(project.join("src/pom.xml").to_string_lossy().to_string()));
+        assert_eq!(config.llm.model.as_deref(), Some("gpt-4o-mini"));
+        assert_eq!(config.llm.temperature, Some(0.7));
+        assert_eq!(config.llm.max_tokens, Some(512));
+        assert_eq!(config.source_root, None);
+        assert_eq!(config.llm.provider, LlmProvider::Ollama);
+        assert_eq!(config.chat_url(), "http://gpu-box:11434/v1/chat/completions");
+        assert_eq!(config.labels, vec!["konveyor.io/target=quarkus", "konveyor.io/source"]);
+        assert_eq!(config.cache_dir, None);
+        assert_eq!(config.migration_path(), MigrationPath::new([], ["quarkus", "eap8"]));
+
+        // Every problem is reported, with where it comes from.
+        fs::write(project.join(PROJECT_FILE), "repots = []\n[llm]\ntemperature = 3.0\n").unwrap();
+        let bad_env = |name: &str| match name {
+            "KAI_OUTPUT_FORMAT" => Some("xml".to_string()),
+            "KAI_LLM_PROVIDER" => Some("claude".to_string()),
+            "KAI_LLM_MAX_TOKENS" => Some("lots".to_string()),
+            _ => env(name),
+        };
+        let error = Config::from_layers(&paths, &bad_env).unwrap_err().to_string();
+        fs::remove_dir_all(&dir).unwrap();
+        assert!(error.contains(&format!("{}: ", project.join(PROJECT_FILE).display())) && error.contains("repots"));
+        assert!(error.contains("KAI_OUTPUT_FORMAT: unknown format xml"));
+        assert!(error.contains("KAI_LLM_PROVIDER: unknown provider claude"));
+        assert!(error.contains("KAI_LLM_MAX_TOKENS: expected a positive integer, not `lots`"));
+    }
+
+    #[test]
+    fn validation_errors() {
+        let error = Config::parse("
+            [filters]
+            include = [\"src/[\"]
+            [output]
+            editor = \"emacs\"
+            [llm]
+            endpoint = \"localhost:11434\"
+            model = \" \"
+            temperature = 2.5
+            max_tokens = 0
+        ").unwrap_err().to_string();
+        let lines: Vec<&str> = error.lines().collect();
+        assert_eq!(lines.len(), 6);
+        assert!(lines[0].starts_with("filters.include: invalid glob `src/[`"));
+        assert_eq!(lines[1], "output.editor: unknown editor emacs, expected vscode or idea");
+        assert_eq!(lines[2], "llm.endpoint: expected an http or https URL, not `localhost:11434`");
+        assert_eq!(lines[3], "llm.model: empty model");
+        assert!(Config::parse("[llm]\nprovider = \"claude\"").is_err());
+        assert!(Config::parse("[output]\nformat = \"xml\"").is_err());
+        assert!(Config::parse("").unwrap() == Config::default());
+    }
+
+    #[test]
+    fn select_and_map_paths() {
+        let report = parse_yaml("samples/demo-output.yaml").unwrap();
+        let config = Config::parse("
+            source_root = \"/examples/java\"
+            [uri_prefixes]
+            \"file:///examples\" = \"/work\"
+            \"file:///examples/java\" = \"/home/me/java/\"
+            [filters]
+            include = [\"/examples/java/**\"]
+            exclude = [\"**/dummy/**\"]
+            [output]
+            editor = \"vscode\"
+        ").unwrap();
+        let selected = config.select(&report).unwrap();
+        let files = selected.impacted_file_names();
+        assert!(!files.is_empty());
+        assert!(files.iter().all(|uri| uri.starts_with("file:///examples/java/") && !uri.contains("/dummy/")));
+
+        assert_eq!(config.local_path("file:///examples/java/pom.xml").as_deref(), Some("/home/me/java/pom.xml"));
+        assert_eq!(config.local_path("file:///examples/golang/go.mod").as_deref(), Some("/work/golang/go.mod"));
+        assert_eq!(config.local_path("file:///other"), None);
+        assert_eq!(config.local_path("file:///examples/java2/pom.xml").as_deref(), Some("/work/java2/pom.xml"));
+        assert_eq!(config.local_path("file:///examplesX/pom.xml"), None);
+        let urls: Vec<String> = locations(&selected, &config.location_options()).into_iter().filter_map(|l| l.url).collect();
+        assert!(!urls.is_empty() && urls.iter().all(|url| url.starts_with("vscode://file/home/me/java/")));
+    }
+
+    #[test]
+    fn load_report_applies_suppressions() {
+        let dir = std::env::temp_dir().join(format!("kai-config-suppressions-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("suppressions.yaml");
+        fs::write(&path, "
+suppressions:
+- rule_id: chain-pom-001
+  uri: \"**/customers-tomcat-legacy/pom.xml\"
+  justification: Reviewed
+").unwrap();
+        let config = Config { suppressions: Some(path.to_string_lossy().to_string()), ..Default::default() };
+        let report = config.load_report("samples/demo-output.yaml");
+        let result = config.load_suppressed("samples/demo-output.yaml", config.load_suppressions().unwrap().as_ref());
+        fs::write(&path, "suppressions:\n- rule_id: chain-pom-001\n").unwrap();
+        let error = config.load_report("samples/demo-output.yaml").unwrap_err().to_string();
+        fs::write(&path, "suppressions:\n- rule_id: chain-pom-001\n  fingerprint: 0123456789abcdef\n  justification: Reviewed\n").unwrap();
+        let missing_root = config.load_report("samples/demo-output.yaml").unwrap_err().to_string();
+        let rooted = Config { source_root: Some("/examples".to_string()), ..config.clone() }.load_report("samples/demo-output.yaml");
+        fs::remove_dir_all(&dir).unwrap();
+
+        let report = report.unwrap();
+        let result = result.unwrap();
+        assert_eq!(report, result.report);
+        assert!(!result.suppressed.is_empty());
+        assert!(result.suppressed.iter().all(|s| &*s.rule_id == "chain-pom-001"));
+        let before = Config::default().load_report("samples/demo-output.yaml").unwrap().violation_counts();
+        assert_eq!(report.violation_counts().get("chain-pom-001").copied().unwrap_or(0), before["chain-pom-001"] - result.suppressed.len());
+        assert!(error.starts_with(&path.to_string_lossy().to_string()) && error.contains("missing justification"));
+        assert!(missing_root.contains("#1 (chain-pom-001): fingerprint entries need source_root to be configured"));
+        assert!(rooted.is_ok());
+    }
+
+    #[test]
+    fn select_keeps_rulesets() {
+        let report = parse_yaml("samples/coolstore_analysis_output.yaml").unwrap();
+        let config = Config::parse("targets = [\"quarkus\"]\nlabels = [\"konveyor.io/source=java-ee\"]").unwrap();
+        let selected = config.select(&report).unwrap();
+        assert_eq!(selected.rulesets.len(), report.rulesets.len());
+        for (selected, ruleset) in selected.rulesets.iter().zip(&report.rulesets) {
+            assert_eq!((&selected.name, &selected.unmatched, &selected.errors), (&ruleset.name, &ruleset.unmatched, &ruleset.errors));
+        }
+        let scoped = report.scope(&config.migration_path()).report.filter_by_label("konveyor.io/source=java-ee");
+        assert!(scoped.rulesets.len() < report.rulesets.len());
+        assert_eq!(selected.violation_counts(), scoped.violation_counts());
+
+        // A hand-built `Config` with a broken glob fails instead of keeping every file.
+        let broken = Config { filters: Filters { include: vec!["[pom.xml".to_string()], ..Default::default() }, ..Default::default() };
+        let error = broken.select(&report).unwrap_err().to_string();
+        assert!(error.starts_with("filters.include: invalid glob `[pom.xml`"));
+    }
+
+    #[test]
+    fn chat_request() {
+        let config = Config::parse("[llm]\nmodel = \"gpt-4o\"\nmax_tokens = 1024").unwrap();
+        let request = serde_json::to_value(config.chat_request("Fix it".to_string()).unwrap()).unwrap();
+        assert_eq!(request, serde_json::json!({
+            "model": "gpt-4o",
+            "messages": [{"role": "user", "content": "Fix it"}],
+            "max_tokens": 1024,
+        }));
+        assert!(Config::default().chat_request(String::new()).is_err());
+        assert_eq!(config.chat_url(), "https://api.openai.com/v1/chat/completions");
+    }
+}
@@ -0,0 +1,123 @@
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use crate::yaml_parser::{AnalysisReport, Incident};
+
+/// An incident together with the rule that raised it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DiffEntry {
+    pub ruleset: Arc<str>,
+    pub rule_id: Arc<str>,
+    pub incident: Incident,
+    pub fingerprint: String,
+}
+
+/// Incidents that appeared or disappeared between two versions of a report.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ReportDiff {
+    pub new: Vec<DiffEntry>,
+    pub resolved: Vec<DiffEntry>,
+    pub unchanged: usize,
+}
+
+impl ReportDiff {
+    pub fn is_empty(&self) -> bool {
+        self.new.is_empty() && self.resolved.is_empty()
+    }
+}
+
+// Incidents are considered the same when rule and fingerprint match, so an
+// incident moved by lines added or removed above it is unchanged.
+type IncidentKey = (Arc<str>, String);
+
+fn entries(report: &AnalysisReport, source_root: Option<&str>) -> BTreeMap<IncidentKey, Vec<DiffEntry>> {
+    let mut entries = BTreeMap::<IncidentKey, Vec<DiffEntry>>::new();
+    for ruleset in &report.rulesets {
+        for (rule_id, violation) in &ruleset.violations {
+            let fingerprints = violation.fingerprints(rule_id, source_root);
+            for (incident, fingerprint) in violation.incidents.iter().zip(fingerprints) {
+                entries.entry((rule_id.clone(), fingerprint.clone())).or_default().push(DiffEntry {
+                    ruleset: ruleset.name.clone(),
+                    rule_id: rule_id.clone(),
+                    incident: incident.clone(),
+                    fingerprint,
+                });
+            }
+        }
+    }
+    entries
+}
+
+/// Compares `current` against `baseline`, matching incidents by fingerprint
+/// taken relative to `source_root`, see `Violation::fingerprints`.
+///
+/// Identical incidents are matched one to one, so a duplicated incident that
+/// loses one copy shows up as resolved once. Entries are sorted by rule ID
+/// and location.
+pub fn diff_reports(baseline: &AnalysisReport, current: &AnalysisReport, source_root: Option<&str>) -> ReportDiff {
+    let mut before = entries(baseline, source_root);
+    let mut diff = ReportDiff::default();
+    for (key, mut after) in entries(current, source_root) {
+        let mut matched = before.remove(&key).unwrap_or_default();
+        let kept = matched.len().min(after.len());
+        diff.unchanged += kept;
+        diff.new.extend(after.drain(kept..));
+        diff.resolved.extend(matched.drain(kept..));
+    }
+    for (_, resolved) in before {
+        diff.resolved.extend(resolved);
+    }
+    for entries in [&mut diff.new, &mut diff.resolved] {
+        entries.sort_by(|a, b| a.rule_id.cmp(&b.rule_id).then(a.incident.cmp_location(&b.incident)));
+    }
+    diff
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::yaml_parser::parse_yaml;
+
+    #[test]
+    fn new_and_resolved_incidents() {
+        let baseline = parse_yaml("samples/demo-output.yaml").unwrap();
+        assert!(diff_reports(&baseline, &baseline, None).is_empty());
+
+        let mut current = baseline.clone();
+        let violations = &mut current.rulesets[0].violations;
+        let removed = violations.get_mut("chain-pom-001").unwrap().incidents.remove(0);
+        let added = Incident {
+            uri: "file:///examples/new/App.java".into(),
+            message: "new".to_string(),
+            line_number: Some(3),
+            ..Default::default()
+        };
+        violations.get_mut("xml-pom-001").unwrap().incidents.push(added.clone());
+
+        let diff = diff_reports(&baseline, &current, None);
+        assert_eq!(diff.new.len(), 1);
+        assert_eq!(diff.new[0].incident, added);
+        assert_eq!(&*diff.new[0].rule_id, "xml-pom-001");
+        assert_eq!(diff.resolved.len(), 1);
+        assert_eq!(diff.resolved[0].incident, removed);
+        assert_eq!(diff.unchanged, baseline.summary().incidents - 1);
+    }
+
+    #[test]
+    fn shifted_lines_are_unchanged() {
+        let baseline = parse_yaml("samples/coolstore_analysis_output.yaml").unwrap();
+        let mut current = baseline.clone();
+        for ruleset in &mut current.rulesets {
+            for violation in ruleset.violations.values_mut() {
+                for incident in &mut violation.incidents {
+                    incident.line_number = incident.line_number.map(|line| line + 3);
+                    incident.code_snip = incident.snippet().map(|snippet| snippet.lines.iter()
+                        .map(|line| format!("{}  {}\n", line.number + 3, line.text))
+                        .collect());
+                }
+            }
+        }
+        let diff = diff_reports(&baseline, &current, Some("/opt/input/source"));
+        assert!(diff.is_empty(), "{} new, {} resolved", diff.new.len(), diff.resolved.len());
+        assert_eq!(diff.unchanged, baseline.summary().incidents);
+    }
+}
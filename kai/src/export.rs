@@ -0,0 +1,153 @@
+use std::io::Write;
+use serde::Serialize;
+use crate::yaml_parser::AnalysisReport;
+
+/// Delimited text formats understood by spreadsheets.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Delimited {
+    Csv,
+    Tsv,
+}
+
+impl Delimited {
+    fn delimiter(&self) -> u8 {
+        match self {
+            Delimited::Csv => b',',
+            Delimited::Tsv => b'\t',
+        }
+    }
+}
+
+/// One incident flattened into a spreadsheet row.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct IncidentRecord {
+    pub application: String,
+    pub ruleset: String,
+    pub rule_id: String,
+    pub category: String,
+    pub effort: Option<i32>,
+    /// Labels separated by `;`.
+    pub labels: String,
+    pub uri: String,
+    pub path: String,
+    pub line: Option<i32>,
+    /// See `Violation::fingerprints`, taken relative to the root when one is
+    /// given, never to `common_root`.
+    pub fingerprint: String,
+    pub message: String,
+}
+
+/// Returns the path of a `file://` URI, or the URI itself for other schemes.
+pub fn uri_path(uri: &str) -> &str {
+    uri.strip_prefix("file://").unwrap_or(uri)
+}
+
+/// Longest directory shared by every incident path of the report, e.g. `/examples/`.
+pub fn common_root(report: &AnalysisReport) -> String {
+    let uris = report.impacted_file_names();
+    let mut paths = uris.iter().map(|uri| uri_path(uri)).filter(|path| !path.is_empty());
+    let Some(first) = paths.next() else {
+        return String::new();
+    };
+    let mut root = &first[..first.rfind('/').map(|i| i + 1).unwrap_or(0)];
+    for path in paths {
+        while !path.starts_with(root) {
+            let trimmed = &root[..root.len() - 1];
+            root = &trimmed[..trimmed.rfind('/').map(|i| i + 1).unwrap_or(0)];
+        }
+    }
+    root.to_string()
+}
+
+/// Returns `uri` as a path relative to `root`, unchanged when it lies outside of it.
+pub fn relative_path(uri: &str, root: &str) -> String {
+    let path = uri_path(uri);
+    let root = root.trim_end_matches('/');
+    match path.strip_prefix(root) {
+        Some(rest) if root.is_empty() || rest.starts_with('/') => rest.trim_start_matches('/').to_string(),
+        _ => path.to_string(),
+    }
+}
+
+/// Flattens `report` into one record per incident, in report order.
+///
+/// Paths are made relative to `root`, or to `common_root` of the report when
+/// no root is given. Fingerprints only use `root` when it is given, so they do
+/// not change with the incidents of the report. Filtering (labels,
+/// suppressions, ...) is expected to be done on the report beforehand.
+pub fn incident_records(application: &str, report: &AnalysisReport, source_root: Option<&str>) -> Vec<IncidentRecord> {
+    let root = source_root.map(str::to_string).unwrap_or_else(|| common_root(report));
+    let mut records = Vec::new();
+    for ruleset in &report.rulesets {
+        for (rule_id, violation) in &ruleset.violations {
+            let fingerprints = violation.fingerprints(rule_id, source_root);
+            for (incident, fingerprint) in violation.incidents.iter().zip(fingerprints) {
+                records.push(IncidentRecord {
+                    application: application.to_string(),
+                    ruleset: ruleset.name.to_string(),
+                    rule_id: rule_id.to_string(),
+                    category: violation.category.clone().unwrap_or_default(),
+                    effort: violation.effort,
+                    labels: violation.labels.join(";"),
+                    uri: incident.uri.to_string(),
+                    path: relative_path(&incident.uri, &root),
+                    line: incident.line_number,
+                    fingerprint,
+                    message: incident.message.clone(),
+                });
+            }
+        }
+    }
+    records
+}
+
+/// Writes `rows` with a header line, quoting fields that contain the
+/// delimiter, quotes or line breaks.
+pub fn write_rows<T: Serialize, W: Write>(rows: &[T], format: Delimited, writer: W) -> Result<(), Box<dyn std::error::Error>> {
+    let mut out = csv::WriterBuilder::new()
+        .delimiter(format.delimiter())
+        .from_writer(writer);
+    for row in rows {
+        out.serialize(row)?;
+    }
+    out.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::yaml_parser::parse_yaml;
+
+    #[test]
+    fn relative_paths() {
+        assert_eq!(relative_path("file:///examples/java/pom.xml", "/examples"), "java/pom.xml");
+        assert_eq!(relative_path("file:///examples/java/pom.xml", "/examples/"), "java/pom.xml");
+        assert_eq!(relative_path("file:///examples-2/pom.xml", "/examples"), "/examples-2/pom.xml");
+        assert_eq!(relative_path("file:///examples/pom.xml", ""), "examples/pom.xml");
+
+        let report = parse_yaml("samples/demo-output.yaml").unwrap();
+        assert_eq!(common_root(&report), "/examples/");
+    }
+
+    #[test]
+    fn csv_and_tsv_round_trip_multiline_messages() {
+        let report = parse_yaml("samples/coolstore_analysis_output.yaml").unwrap();
+        let records = incident_records("coolstore", &report, None);
+        assert_eq!(records.len(), report.summary().incidents);
+        assert!(records.iter().any(|r| r.message.contains('\n')));
+
+        for format in [Delimited::Csv, Delimited::Tsv] {
+            let mut out = Vec::new();
+            write_rows(&records, format, &mut out).unwrap();
+            let mut reader = csv::ReaderBuilder::new()
+                .delimiter(format.delimiter())
+                .from_reader(out.as_slice());
+            let headers = reader.headers().unwrap().clone();
+            assert_eq!(headers.iter().collect::<Vec<_>>(),
+                vec!["application", "ruleset", "rule_id", "category", "effort", "labels", "uri", "path", "line", "fingerprint", "message"]);
+            let messages: Vec<String> = reader.records().map(|r| r.unwrap()[10].to_string()).collect();
+            assert_eq!(messages, records.iter().map(|r| r.message.clone()).collect::<Vec<_>>());
+        }
+    }
+}
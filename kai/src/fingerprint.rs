@@ -0,0 +1,185 @@
+use std::fmt::Write;
+use crate::cache::source_hash;
+use crate::export::{relative_path, uri_path};
+use crate::yaml_parser::{Incident, Violation};
+
+/// Snippet lines kept on each side of the flagged line when fingerprinting.
+pub const FINGERPRINT_CONTEXT: usize = 2;
+
+/// Hex digits of a fingerprint.
+pub const FINGERPRINT_LENGTH: usize = 16;
+
+// Collapses every run of whitespace into one space.
+fn squash(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// `path` with forward slashes and without leading `./` or `/`, so the same
+/// file gets the same path whatever the platform of the analysis.
+pub fn normalize_path(path: &str) -> String {
+    let path = path.replace('\\', "/");
+    let mut path = path.as_str();
+    while let Some(rest) = path.strip_prefix("./").or_else(|| path.strip_prefix('/')) {
+        path = rest;
+    }
+    path.to_string()
+}
+
+// The code the fingerprint is computed on: the flagged line, marked, and its
+// context without line numbers, or the message when there is no snippet.
+fn fingerprint_code(incident: &Incident) -> String {
+    match incident.snippet() {
+        Some(snippet) => snippet.trim(FINGERPRINT_CONTEXT).lines.iter()
+            .map(|line| if line.flagged { format!("> {}", squash(&line.text)) } else { squash(&line.text) })
+            .filter(|line| !line.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n"),
+        None => squash(&incident.message),
+    }
+}
+
+// Hash of the fingerprint input, `path` being normalized already.
+fn hash(rule_id: &str, path: &str, code: &str, occurrence: usize) -> String {
+    let mut input = format!("{}\n{}\n{}", rule_id, path, code);
+    // The first occurrence hashes as if it were unique.
+    if occurrence > 0 {
+        let _ = write!(input, "\n#{}", occurrence);
+    }
+    source_hash(input.as_bytes())[..FINGERPRINT_LENGTH].to_string()
+}
+
+/// Stable identifier of an incident of `rule_id` in the file at `path`,
+/// `occurrence` being its position, in line order, among the incidents of
+/// the rule flagging the same code in that file.
+///
+/// The hash covers the rule, the normalized path and the whitespace
+/// normalized code around the flagged line, not the line number, so the
+/// fingerprint survives lines added or removed elsewhere in the file and
+/// reindentation.
+pub fn fingerprint(rule_id: &str, path: &str, incident: &Incident, occurrence: usize) -> String {
+    hash(rule_id, &normalize_path(path), &fingerprint_code(incident), occurrence)
+}
+
+impl Violation {
+    /// Fingerprints of the incidents of this violation of `rule_id`, in
+    /// incident order, see `fingerprint`. Identical code flagged more than
+    /// once in a file is told apart by its occurrence, so every incident
+    /// gets its own fingerprint.
+    ///
+    /// Paths are taken relative to the configured `source_root`. The root
+    /// must not be derived from the report, as `common_root` is: it moves
+    /// with the incidents a run or a filter keeps, and the fingerprints
+    /// would move with it. Without a root the full path of the URI is
+    /// hashed, so fingerprints change with the directory the sources were
+    /// analyzed in, which is why triage and fingerprint suppressions need one.
+    pub fn fingerprints(&self, rule_id: &str, source_root: Option<&str>) -> Vec<String> {
+        let keys: Vec<(String, String)> = self.incidents
+            .iter()
+            .map(|incident| {
+                let path = match source_root {
+                    Some(root) => relative_path(&incident.uri, root),
+                    None => uri_path(&incident.uri).to_string(),
+                };
+                (normalize_path(&path), fingerprint_code(incident))
+            })
+            .collect();
+        // Incidents with the same path and code end up next to each other, in line order.
+        let mut order: Vec<usize> = (0..keys.len()).collect();
+        order.sort_by(|a, b| (&keys[*a], self.incidents[*a].line_number).cmp(&(&keys[*b], self.incidents[*b].line_number)));
+        let mut occurrences = vec![0; keys.len()];
+        for pair in order.windows(2) {
+            if keys[pair[0]] == keys[pair[1]] {
+                occurrences[pair[1]] = occurrences[pair[0]] + 1;
+            }
+        }
+        keys.iter()
+            .zip(occurrences)
+            .map(|((path, code), occurrence)| hash(rule_id, path, code, occurrence))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::{common_root, incident_records};
+    use crate::yaml_parser::{parse_yaml, AnalysisReport};
+
+    #[test]
+    fn fingerprints_survive_line_shifts_and_reindentation() {
+        let report = parse_yaml("samples/coolstore_analysis_output.yaml").unwrap();
+        let rule_id = "javax-to-jakarta-import-00001";
+        let violation = report.rulesets.iter().find_map(|r| r.violations.get(rule_id)).unwrap();
+        let incident = violation.incidents.iter().find(|i| i.code_snip.is_some()).unwrap();
+        let root = Some("/opt/input/source");
+        let single = |incident: &Incident, rule_id: &str, root: Option<&str>| {
+            Violation { incidents: vec![incident.clone()], ..Default::default() }.fingerprints(rule_id, root).remove(0)
+        };
+        let id = single(incident, rule_id, root);
+        assert_eq!(id.len(), FINGERPRINT_LENGTH);
+
+        // The same code ten lines further down, indented with tabs.
+        let line = incident.line_number.unwrap();
+        let mut shifted = incident.clone();
+        shifted.line_number = Some(line + 10);
+        shifted.code_snip = incident.snippet().map(|snippet| snippet.lines.iter()
+            .map(|l| format!("{}  {}\n", l.number + 10, l.text.replace("    ", "\t")))
+            .collect());
+        assert_eq!(single(&shifted, rule_id, root), id);
+        assert_eq!(fingerprint(rule_id, &format!(".\\{}", relative_path(&incident.uri, "/opt/input/source").replace('/', "\\")), incident, 0), id);
+
+        assert_ne!(single(incident, "other-rule", root), id);
+        assert_ne!(single(incident, rule_id, Some("/opt/input")), id);
+        assert_eq!(single(incident, rule_id, None), fingerprint(rule_id, uri_path(&incident.uri), incident, 0));
+        let mut edited = incident.clone();
+        edited.code_snip = incident.code_snip.as_ref().map(|code| code.replace("javax", "jakarta"));
+        assert_ne!(single(&edited, rule_id, root), id);
+
+        let mut ids = violation.fingerprints(rule_id, root);
+        ids.sort();
+        ids.dedup();
+        assert_eq!(ids.len(), violation.incidents.len());
+    }
+
+    #[test]
+    fn identical_incidents_are_told_apart_in_line_order() {
+        let incident = Incident {
+            uri: "file:///app/src/App.java".into(),
+            message: "Replace `javax.ejb`".to_string(),
+            line_number: Some(20),
+            ..Default::default()
+        };
+        let later = Incident { line_number: Some(40), ..incident.clone() };
+        let other = Incident { uri: "file:///app/src/Other.java".into(), ..incident.clone() };
+        let violation = Violation { incidents: vec![later.clone(), other, incident.clone()], ..Default::default() };
+        let ids = violation.fingerprints("ejb-00001", Some("/app"));
+        assert_eq!(ids[2], fingerprint("ejb-00001", "src/App.java", &incident, 0));
+        assert_eq!(ids[0], fingerprint("ejb-00001", "src/App.java", &later, 1));
+        assert_eq!(ids[1], fingerprint("ejb-00001", "src/Other.java", &incident, 0));
+        assert_ne!(ids[0], ids[2]);
+
+        // Lines added above both keep their fingerprints.
+        let shifted = Violation {
+            incidents: violation.incidents.iter().map(|i| Incident { line_number: i.line_number.map(|l| l + 5), ..i.clone() }).collect(),
+            ..Default::default()
+        };
+        assert_eq!(shifted.fingerprints("ejb-00001", Some("/app")), ids);
+    }
+
+    #[test]
+    fn fingerprints_do_not_depend_on_the_other_incidents() {
+        let report = parse_yaml("samples/demo-output.yaml").unwrap();
+        let filtered = report.filter_by_label("testing");
+        // Filtering moves the common root, e.g. from `/examples/` to `/examples/golang/`.
+        assert_ne!(common_root(&filtered), common_root(&report));
+        let ids = |report: &AnalysisReport| -> Vec<(String, String)> {
+            incident_records("demo", report, None).into_iter()
+                .map(|record| (format!("{} {} {:?} {}", record.rule_id, record.uri, record.line, record.message), record.fingerprint))
+                .collect()
+        };
+        let all = ids(&report);
+        let kept = ids(&filtered);
+        assert!(!kept.is_empty());
+        assert!(kept.iter().all(|id| all.contains(id)));
+    }
+}
@@ -0,0 +1,291 @@
+use std::collections::BTreeSet;
+use rusqlite::{params, Connection};
+use crate::yaml_parser::Incident;
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS fixes (
+        id          INTEGER PRIMARY KEY AUTOINCREMENT,
+        rule_id     TEXT NOT NULL,
+        file        TEXT NOT NULL,
+        before      TEXT NOT NULL,
+        after       TEXT NOT NULL,
+        explanation TEXT NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS idx_fixes_rule ON fixes(rule_id);
+";
+
+/// An accepted change that resolved an incident of `rule_id`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FixExample {
+    /// Id in the `FixStore`, 0 until the example is added.
+    pub id: i64,
+    pub rule_id: String,
+    pub file: String,
+    pub before: String,
+    pub after: String,
+    pub explanation: String,
+}
+
+impl FixExample {
+
+    /// Splits a unified diff into one example per hunk.
+    ///
+    /// `before` holds the context and removed lines of the hunk, `after` the
+    /// context and added lines, so both read as plain source.
+    pub fn from_patch(rule_id: &str, patch: &str, explanation: &str) -> Vec<FixExample> {
+        let mut examples = Vec::new();
+        let mut file = String::new();
+        let mut hunk: Option<FixExample> = None;
+        // Old and new lines left in the current hunk. Headers are only looked
+        // for once both run out, as `--- ` may as well be a removed `-- ` line.
+        let (mut old, mut new) = (0, 0);
+        for line in patch.lines() {
+            if old == 0 && new == 0 {
+                examples.extend(hunk.take());
+                // Deleted files are named by the old path, `+++ /dev/null` is skipped.
+                if let Some(path) = line.strip_prefix("--- ") {
+                    file = header_path(path, "a/");
+                } else if let Some(path) = line.strip_prefix("+++ ").map(|path| header_path(path, "b/")) {
+                    if path != "/dev/null" {
+                        file = path;
+                    }
+                } else if let Some((o, n)) = hunk_lengths(line) {
+                    (old, new) = (o, n);
+                    hunk = Some(FixExample {
+                        rule_id: rule_id.to_string(),
+                        file: file.clone(),
+                        explanation: explanation.to_string(),
+                        ..Default::default()
+                    });
+                }
+                continue;
+            }
+            let Some(example) = hunk.as_mut() else { continue };
+            let (marker, text) = line.split_at(line.chars().next().map_or(0, char::len_utf8));
+            match marker {
+                // Some tools strip the space of empty context lines.
+                " " | "" => {
+                    push_line(&mut example.before, text);
+                    push_line(&mut example.after, text);
+                    old -= 1;
+                    new -= 1;
+                }
+                "-" => {
+                    push_line(&mut example.before, text);
+                    old -= 1;
+                }
+                "+" => {
+                    push_line(&mut example.after, text);
+                    new -= 1;
+                }
+                // `\ No newline at end of file`
+                _ => {}
+            }
+            if old < 0 || new < 0 {
+                // Malformed hunk, keep what was read and resync on the next header.
+                (old, new) = (0, 0);
+            }
+        }
+        examples.extend(hunk);
+        examples.retain(|example| example.before != example.after);
+        examples
+    }
+}
+
+// Path of a `--- ` or `+++ ` header, without its timestamp and `a/` or `b/` prefix.
+fn header_path(path: &str, prefix: &str) -> String {
+    let path = path.split('\t').next().unwrap_or(path);
+    path.strip_prefix(prefix).unwrap_or(path).to_string()
+}
+
+// Line counts of a `@@ -start,old +start,new @@` header, a missing count meaning 1.
+fn hunk_lengths(line: &str) -> Option<(i64, i64)> {
+    let mut ranges = line.strip_prefix("@@ ")?.split(' ');
+    let count = |range: Option<&str>, sign: char| -> Option<i64> {
+        let range = range?.strip_prefix(sign)?;
+        match range.split_once(',') {
+            Some((_, count)) => count.parse().ok(),
+            None => Some(1),
+        }
+    };
+    Some((count(ranges.next(), '-')?, count(ranges.next(), '+')?))
+}
+
+fn push_line(text: &mut String, line: &str) {
+    text.push_str(line);
+    text.push('\n');
+}
+
+/// A stored fix and how close it is to the code being fixed.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RankedFix {
+    /// Jaccard similarity of the identifiers, between 0 and 1.
+    pub score: f64,
+    pub fix: FixExample,
+}
+
+/// Identifiers and keywords of `code`, the words lexical similarity is computed on.
+pub fn tokens(code: &str) -> BTreeSet<&str> {
+    code.split(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .filter(|token| !token.is_empty() && !token.chars().all(|c| c.is_ascii_digit()))
+        .collect()
+}
+
+/// Jaccard similarity of the tokens of `a` and `b`.
+pub fn similarity(a: &str, b: &str) -> f64 {
+    let (a, b) = (tokens(a), tokens(b));
+    let union = a.union(&b).count();
+    if union == 0 {
+        return 0.0;
+    }
+    a.intersection(&b).count() as f64 / union as f64
+}
+
+/// The code an incident points at, as compared with past fixes: the
+/// snippet around the flagged line without its line numbers, or the
+/// message when there is no snippet.
+pub fn incident_code(incident: &Incident, context: usize) -> String {
+    match incident.snippet() {
+        Some(snippet) => snippet.trim(context).lines.iter().map(|line| format!("{}\n", line.text)).collect(),
+        None => incident.message.clone(),
+    }
+}
+
+/// SQLite backed knowledge base of past fixes, keyed by rule ID.
+pub struct FixStore {
+    conn: Connection,
+}
+
+impl FixStore {
+
+    pub fn open(path: &str) -> Result<FixStore, Box<dyn std::error::Error>> {
+        FixStore::init(Connection::open(path)?)
+    }
+
+    pub fn open_in_memory() -> Result<FixStore, Box<dyn std::error::Error>> {
+        FixStore::init(Connection::open_in_memory()?)
+    }
+
+    fn init(conn: Connection) -> Result<FixStore, Box<dyn std::error::Error>> {
+        conn.execute_batch(SCHEMA)?;
+        Ok(FixStore { conn })
+    }
+
+    /// Stores `example` and returns its id.
+    pub fn add(&mut self, example: &FixExample) -> Result<i64, Box<dyn std::error::Error>> {
+        self.conn.execute(
+            "INSERT INTO fixes (rule_id, file, before, after, explanation) VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![example.rule_id, example.file, example.before, example.after, example.explanation],
+        )?;
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    pub fn remove(&mut self, id: i64) -> Result<(), Box<dyn std::error::Error>> {
+        self.conn.execute("DELETE FROM fixes WHERE id = ?1", params![id])?;
+        Ok(())
+    }
+
+    /// Every stored fix, or only those of `rule_id`, oldest first.
+    pub fn fixes(&self, rule_id: Option<&str>) -> Result<Vec<FixExample>, Box<dyn std::error::Error>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, rule_id, file, before, after, explanation FROM fixes
+             WHERE ?1 IS NULL OR rule_id = ?1 ORDER BY id")?;
+        let fixes = stmt
+            .query_map(params![rule_id], |row| {
+                Ok(FixExample {
+                    id: row.get(0)?,
+                    rule_id: row.get(1)?,
+                    file: row.get(2)?,
+                    before: row.get(3)?,
+                    after: row.get(4)?,
+                    explanation: row.get(5)?,
+                })
+            })?
+            .collect::<Result<Vec<FixExample>, _>>()?;
+        Ok(fixes)
+    }
+
+    /// The `limit` fixes of `rule_id` whose `before` code is most similar to
+    /// `code`. Ties go to the most recent fix.
+    pub fn relevant(&self, rule_id: &str, code: &str, limit: usize) -> Result<Vec<RankedFix>, Box<dyn std::error::Error>> {
+        let mut ranked: Vec<RankedFix> = self.fixes(Some(rule_id))?
+            .into_iter()
+            .map(|fix| RankedFix { score: similarity(code, &fix.before), fix })
+            .collect();
+        ranked.sort_by(|a, b| b.score.total_cmp(&a.score).then(b.fix.id.cmp(&a.fix.id)));
+        ranked.truncate(limit);
+        Ok(ranked)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PATCH: &str = "\
+diff --git a/src/main/java/com/redhat/coolstore/model/InventoryEntity.java b/src/main/java/com/redhat/coolstore/model/InventoryEntity.java
+--- a/src/main/java/com/redhat/coolstore/model/InventoryEntity.java
++++ b/src/main/java/com/redhat/coolstore/model/InventoryEntity.java
+@@ -4,3 +4,3 @@
+
+-import javax.persistence.Column;
+-import javax.persistence.Entity;
++import jakarta.persistence.Column;
++import jakarta.persistence.Entity;
+@@ -20,2 +20,2 @@
+-    @javax.inject.Inject
++    @jakarta.inject.Inject
+     Logger log;
+";
+
+    #[test]
+    fn patch_hunks_become_examples() {
+        let examples = FixExample::from_patch("javax-to-jakarta-import-00001", PATCH, "Use the jakarta namespace");
+        assert_eq!(examples.len(), 2);
+        assert_eq!(examples[0].file, "src/main/java/com/redhat/coolstore/model/InventoryEntity.java");
+        assert_eq!(examples[0].before, "\nimport javax.persistence.Column;\nimport javax.persistence.Entity;\n");
+        assert_eq!(examples[0].after, "\nimport jakarta.persistence.Column;\nimport jakarta.persistence.Entity;\n");
+        assert_eq!(examples[1].after, "    @jakarta.inject.Inject\n    Logger log;\n");
+    }
+
+    #[test]
+    fn deleted_and_added_files_keep_their_path() {
+        let patch = "\
+--- a/src/main/resources/META-INF/persistence.xml
++++ /dev/null
+@@ -1,2 +0,0 @@
+-<persistence version=\"2.1\">
+-</persistence>
+--- /dev/null
++++ b/src/main/resources/application.properties
+@@ -0,0 +1 @@
++quarkus.datasource.db-kind=h2
+";
+        let examples = FixExample::from_patch("jpa-to-quarkus-00010", patch, "Configure the datasource in Quarkus");
+        assert_eq!(examples.len(), 2);
+        assert_eq!(examples[0].file, "src/main/resources/META-INF/persistence.xml");
+        assert_eq!(examples[0].after, "");
+        assert_eq!(examples[1].file, "src/main/resources/application.properties");
+        assert_eq!(examples[1].after, "quarkus.datasource.db-kind=h2\n");
+    }
+
+    #[test]
+    fn most_similar_fixes_first() {
+        let mut store = FixStore::open_in_memory().unwrap();
+        let rule = "javax-to-jakarta-import-00001";
+        let mut ids = Vec::new();
+        for example in FixExample::from_patch(rule, PATCH, "Use the jakarta namespace") {
+            ids.push(store.add(&example).unwrap());
+        }
+        store.add(&FixExample { rule_id: "other".to_string(), before: "import javax.persistence.Id;".to_string(), ..Default::default() }).unwrap();
+        assert_eq!(store.fixes(None).unwrap().len(), 3);
+
+        let ranked = store.relevant(rule, "import javax.persistence.Id;\nimport javax.persistence.Table;\n", 5).unwrap();
+        assert_eq!(ranked.iter().map(|r| r.fix.id).collect::<Vec<_>>(), ids);
+        assert!(ranked[0].score > ranked[1].score);
+        assert_eq!(store.relevant(rule, "@javax.inject.Inject Logger log;", 1).unwrap()[0].fix.id, ids[1]);
+
+        store.remove(ids[1]).unwrap();
+        assert_eq!(store.fixes(Some(rule)).unwrap().len(), 1);
+    }
+}
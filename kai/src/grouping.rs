@@ -0,0 +1,198 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+use serde::Serialize;
+use crate::export::{common_root, relative_path};
+use crate::yaml_parser::AnalysisReport;
+
+const BUILD_FILES: [&str; 3] = ["pom.xml", "build.gradle", "build.gradle.kts"];
+
+/// Key incidents can be grouped by.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GroupBy {
+    /// Closest directory holding a `pom.xml` or `build.gradle`.
+    Module,
+    /// Java package, taken from the path below a `java/` source directory.
+    Package,
+    /// File extension.
+    Extension,
+}
+
+impl GroupBy {
+    pub fn parse(name: &str) -> Option<GroupBy> {
+        match name {
+            "module" => Some(GroupBy::Module),
+            "package" => Some(GroupBy::Package),
+            "extension" => Some(GroupBy::Extension),
+            _ => None,
+        }
+    }
+}
+
+/// Incidents sharing a key, with effort rolled up from the nested groups.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct Group {
+    pub name: String,
+    pub files: BTreeSet<String>,
+    pub rules: BTreeSet<String>,
+    pub incidents: usize,
+    pub effort: i64,
+    pub children: BTreeMap<String, Group>,
+}
+
+impl Group {
+    fn add(&mut self, keys: &[String], path: &str, rule_id: &str, effort: i64) {
+        self.files.insert(path.to_string());
+        self.rules.insert(rule_id.to_string());
+        self.incidents += 1;
+        self.effort += effort;
+        if let Some((key, rest)) = keys.split_first() {
+            let child = self.children.entry(key.clone()).or_insert_with(|| Group { name: key.clone(), ..Default::default() });
+            child.add(rest, path, rule_id, effort);
+        }
+    }
+
+    /// Nested groups with the most effort first.
+    pub fn by_effort(&self) -> Vec<&Group> {
+        let mut children: Vec<&Group> = self.children.values().collect();
+        children.sort_by(|a, b| b.effort.cmp(&a.effort).then(a.name.cmp(&b.name)));
+        children
+    }
+
+    /// Indented listing of the nested groups, one line per group.
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        self.render_children(0, &mut out);
+        out
+    }
+
+    fn render_children(&self, depth: usize, out: &mut String) {
+        for child in self.by_effort() {
+            let _ = writeln!(out, "{:indent$}{}  effort {}, {} incident(s), {} file(s), {} rule(s)",
+                "", child.name, child.effort, child.incidents, child.files.len(), child.rules.len(), indent = depth * 2);
+            child.render_children(depth + 1, out);
+        }
+    }
+}
+
+/// Directories holding a build file, relative to `root`, longest first.
+pub fn module_roots(report: &AnalysisReport, root: &str) -> Vec<String> {
+    let mut modules = BTreeSet::new();
+    for uri in report.impacted_file_names() {
+        let path = relative_path(&uri, root);
+        let (dir, file) = path.rsplit_once('/').unwrap_or(("", &path));
+        if BUILD_FILES.contains(&file) {
+            modules.insert(dir.to_string());
+        }
+    }
+    let mut modules: Vec<String> = modules.into_iter().collect();
+    modules.sort_by(|a, b| b.len().cmp(&a.len()).then(a.cmp(b)));
+    modules
+}
+
+fn module_of(path: &str, modules: &[String]) -> String {
+    modules
+        .iter()
+        .find(|module| module.is_empty() || path.starts_with(&format!("{}/", module)))
+        .map(|module| if module.is_empty() { ".".to_string() } else { module.clone() })
+        .unwrap_or_else(|| "(no module)".to_string())
+}
+
+/// Java package of a source path, e.g. `com.example.apps` for
+/// `app/src/main/java/com/example/apps/App.java`.
+pub fn package_of(path: &str) -> String {
+    if !path.ends_with(".java") {
+        return "(no package)".to_string();
+    }
+    let dir = format!("/{}/", path.rsplit_once('/').map(|(dir, _)| dir).unwrap_or_default());
+    // The first source directory wins, so a package may have `java` segments of its own.
+    let start = ["/src/main/java/", "/src/test/java/"].iter()
+        .filter_map(|root| dir.find(root).map(|index| index + root.len()))
+        .min()
+        .or_else(|| dir.find("/java/").map(|index| index + "/java/".len()));
+    let Some(start) = start else {
+        // Sources outside of a source directory, e.g. decompiled dependencies.
+        return "(unknown package)".to_string();
+    };
+    let package = dir[start..].trim_end_matches('/');
+    if package.is_empty() {
+        "(default package)".to_string()
+    } else {
+        package.replace('/', ".")
+    }
+}
+
+fn extension_of(path: &str) -> String {
+    let file = path.rsplit('/').next().unwrap_or(path);
+    match file.rsplit_once('.') {
+        Some((name, extension)) if !name.is_empty() => extension.to_string(),
+        _ => "(no extension)".to_string(),
+    }
+}
+
+/// Groups the incidents of `report` by `keys`, nesting one level per key.
+///
+/// Paths are taken relative to `root`, or to the common root of the report.
+/// Effort is the violation effort counted once per incident, as in `summary`.
+pub fn group_incidents(report: &AnalysisReport, keys: &[GroupBy], root: Option<&str>) -> Group {
+    let root = root.map(str::to_string).unwrap_or_else(|| common_root(report));
+    let modules = module_roots(report, &root);
+    let mut tree = Group { name: "all".to_string(), ..Default::default() };
+    for ruleset in &report.rulesets {
+        for (rule_id, violation) in &ruleset.violations {
+            let effort = violation.effort.unwrap_or(0) as i64;
+            for incident in &violation.incidents {
+                let path = relative_path(&incident.uri, &root);
+                let names: Vec<String> = keys
+                    .iter()
+                    .map(|key| match key {
+                        GroupBy::Module => module_of(&path, &modules),
+                        GroupBy::Package => package_of(&path),
+                        GroupBy::Extension => extension_of(&path),
+                    })
+                    .collect();
+                tree.add(&names, &path, rule_id, effort);
+            }
+        }
+    }
+    tree
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::yaml_parser::parse_yaml;
+
+    #[test]
+    fn path_keys() {
+        assert_eq!(package_of("app/src/main/java/com/example/apps/App.java"), "com.example.apps");
+        assert_eq!(package_of("app/src/main/java/App.java"), "(default package)");
+        assert_eq!(package_of("src/main/java/org/java/acme/Util.java"), "org.java.acme");
+        assert_eq!(package_of("app/src/test/java/com/example/AppTest.java"), "com.example");
+        assert_eq!(package_of("java/com/example/App.java"), "com.example");
+        assert_eq!(package_of("lib/com/example/App.java"), "(unknown package)");
+        assert_eq!(package_of("app/pom.xml"), "(no package)");
+        assert_eq!(extension_of("app/pom.xml"), "xml");
+        assert_eq!(extension_of("app/.gitignore"), "(no extension)");
+
+        let modules = vec!["parent/child".to_string(), "parent".to_string()];
+        assert_eq!(module_of("parent/child/src/App.java", &modules), "parent/child");
+        assert_eq!(module_of("parent/childish/App.java", &modules), "parent");
+        assert_eq!(module_of("other/App.java", &modules), "(no module)");
+    }
+
+    #[test]
+    fn effort_rolls_up_through_modules() {
+        let report = parse_yaml("samples/coolstore_analysis_output.yaml").unwrap();
+        let tree = group_incidents(&report, &[GroupBy::Module, GroupBy::Package], None);
+        let summary = report.summary();
+        assert_eq!(tree.incidents, summary.incidents);
+        assert_eq!(tree.effort, summary.effort);
+        for module in tree.children.values() {
+            assert_eq!(module.effort, module.children.values().map(|p| p.effort).sum::<i64>());
+            assert_eq!(module.incidents, module.children.values().map(|p| p.incidents).sum::<usize>());
+        }
+        let efforts: Vec<i64> = tree.by_effort().iter().map(|g| g.effort).collect();
+        assert!(efforts.windows(2).all(|w| w[0] >= w[1]));
+        assert_eq!(tree.render().lines().count(), tree.children.len() + tree.children.values().map(|m| m.children.len()).sum::<usize>());
+    }
+}
@@ -0,0 +1,134 @@
+use std::fs::File;
+use std::io::Read;
+use crate::yaml_parser::{AnalysisReport, Ruleset};
+
+/// Compression of an analyzer output, detected from its first bytes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Compression {
+    None,
+    Gzip,
+    Zstd,
+}
+
+impl Compression {
+    pub fn detect(bytes: &[u8]) -> Compression {
+        if bytes.starts_with(&[0x1f, 0x8b]) {
+            Compression::Gzip
+        } else if bytes.starts_with(&[0x28, 0xb5, 0x2f, 0xfd]) {
+            Compression::Zstd
+        } else {
+            Compression::None
+        }
+    }
+}
+
+/// Serialization of an analyzer output.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    Yaml,
+    Json,
+}
+
+impl Format {
+    /// JSON output always starts with the ruleset list, YAML output with a
+    /// `- name:` item. Flow style YAML also starts with `[`, so JSON
+    /// detection is only a first guess, see `parse_rulesets`.
+    pub fn detect(contents: &str) -> Format {
+        match contents.trim_start_matches('\u{feff}').trim_start().chars().next() {
+            Some('[') | Some('{') => Format::Json,
+            _ => Format::Yaml,
+        }
+    }
+}
+
+fn decompress(bytes: Vec<u8>) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    let mut out = Vec::new();
+    match Compression::detect(&bytes) {
+        Compression::None => return Ok(bytes),
+        Compression::Gzip => {
+            flate2::read::MultiGzDecoder::new(bytes.as_slice()).read_to_end(&mut out)?;
+        }
+        Compression::Zstd => {
+            zstd::stream::read::Decoder::new(bytes.as_slice())?.read_to_end(&mut out)?;
+        }
+    }
+    Ok(out)
+}
+
+// JSON input is a list of rulesets like the YAML output, or an object with a
+// `rulesets` field as written by `AnalysisReport` itself.
+fn parse_rulesets(contents: &str) -> Result<Vec<Ruleset>, Box<dyn std::error::Error>> {
+    if Format::detect(contents) == Format::Json {
+        if let Ok(rulesets) = serde_json::from_str::<Vec<Ruleset>>(contents) {
+            return Ok(rulesets);
+        }
+        if let Ok(report) = serde_json::from_str::<AnalysisReport>(contents) {
+            return Ok(report.rulesets);
+        }
+    }
+    Ok(serde_yaml::from_str(contents)?)
+}
+
+impl AnalysisReport {
+
+    /// Reads an analyzer output from `reader`.
+    ///
+    /// YAML and JSON are both accepted, optionally gzip or zstd compressed;
+    /// the format is detected from the content, not from a file name.
+    pub fn from_reader<R: Read>(mut reader: R) -> Result<AnalysisReport, Box<dyn std::error::Error>> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes)?;
+        let contents = String::from_utf8(decompress(bytes)?)?;
+        let mut report = AnalysisReport { rulesets: parse_rulesets(&contents)? };
+        report.sort_incidents();
+        report.intern();
+        Ok(report)
+    }
+
+    /// Reads an analyzer output from `path`, or from stdin when `path` is `-`.
+    pub fn open(path: &str) -> Result<AnalysisReport, Box<dyn std::error::Error>> {
+        if path == "-" {
+            AnalysisReport::from_reader(std::io::stdin().lock())
+        } else {
+            AnalysisReport::from_reader(File::open(path)?)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use crate::yaml_parser::parse_yaml;
+
+    #[test]
+    fn compressed_inputs() {
+        let yaml = std::fs::read("samples/demo-output.yaml").unwrap();
+        let expected = parse_yaml("samples/demo-output.yaml").unwrap();
+
+        let mut gzip = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        gzip.write_all(&yaml).unwrap();
+        let gzip = gzip.finish().unwrap();
+        assert_eq!(Compression::detect(&gzip), Compression::Gzip);
+        assert_eq!(AnalysisReport::from_reader(gzip.as_slice()).unwrap(), expected);
+
+        let zstd = zstd::encode_all(yaml.as_slice(), 0).unwrap();
+        assert_eq!(Compression::detect(&zstd), Compression::Zstd);
+        assert_eq!(AnalysisReport::from_reader(zstd.as_slice()).unwrap(), expected);
+    }
+
+    #[test]
+    fn json_inputs() {
+        let expected = parse_yaml("samples/demo-output.yaml").unwrap();
+        let list = serde_json::to_string(&expected.rulesets).unwrap();
+        let object = serde_json::to_string_pretty(&expected).unwrap();
+        assert_eq!(Format::detect(&object), Format::Json);
+        assert_eq!(AnalysisReport::from_reader(list.as_bytes()).unwrap(), expected);
+        assert_eq!(AnalysisReport::from_reader(object.as_bytes()).unwrap(), expected);
+
+        // Flow style YAML is not JSON but parses as YAML.
+        let flow = AnalysisReport::from_reader("[{name: flow, tags: [a]}]".as_bytes()).unwrap();
+        assert_eq!(flow.rulesets[0].tags, vec!["a"]);
+        assert!(AnalysisReport::from_reader("[{".as_bytes()).is_err());
+    }
+}
@@ -0,0 +1,104 @@
+use std::collections::{BTreeMap, HashSet};
+use std::sync::Arc;
+use crate::yaml_parser::{AnalysisReport, Incident};
+
+/// Pool of shared strings, handing out one `Arc<str>` per distinct value.
+#[derive(Debug, Default)]
+pub struct Interner {
+    strings: HashSet<Arc<str>>,
+}
+
+impl Interner {
+
+    /// Returns the pooled copy of `value`, adding `value` itself when it is new.
+    pub fn intern(&mut self, value: &Arc<str>) -> Arc<str> {
+        match self.strings.get(&**value) {
+            Some(pooled) => pooled.clone(),
+            None => {
+                self.strings.insert(value.clone());
+                value.clone()
+            }
+        }
+    }
+
+    /// Number of distinct strings in the pool.
+    pub fn len(&self) -> usize {
+        self.strings.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.strings.is_empty()
+    }
+
+    fn intern_keys<V>(&mut self, map: BTreeMap<Arc<str>, V>) -> BTreeMap<Arc<str>, V> {
+        map.into_iter().map(|(key, value)| (self.intern(&key), value)).collect()
+    }
+
+    fn intern_all(&mut self, values: &mut [Arc<str>]) {
+        for value in values {
+            *value = self.intern(value);
+        }
+    }
+
+    fn intern_incidents(&mut self, incidents: &mut [Incident]) {
+        for incident in incidents {
+            incident.uri = self.intern(&incident.uri);
+        }
+    }
+}
+
+impl AnalysisReport {
+
+    /// Makes equal uris, rule IDs, labels and ruleset names share one allocation.
+    ///
+    /// Deserializing allocates every string on its own, so the loaders call
+    /// this right after parsing. The content of the report does not change.
+    pub fn intern(&mut self) {
+        self.intern_with(&mut Interner::default());
+    }
+
+    /// Same as `intern`, with a pool that can be shared between reports.
+    pub fn intern_with(&mut self, interner: &mut Interner) {
+        for ruleset in &mut self.rulesets {
+            ruleset.name = interner.intern(&ruleset.name);
+            ruleset.violations = interner.intern_keys(std::mem::take(&mut ruleset.violations));
+            ruleset.insights = interner.intern_keys(std::mem::take(&mut ruleset.insights));
+            for violation in ruleset.violations.values_mut() {
+                interner.intern_all(&mut violation.labels);
+                interner.intern_incidents(&mut violation.incidents);
+            }
+            for insight in ruleset.insights.values_mut() {
+                interner.intern_all(&mut insight.labels);
+                interner.intern_incidents(&mut insight.incidents);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn equal_strings_share_one_allocation() {
+        let yaml = std::fs::read_to_string("samples/demo-output.yaml").unwrap();
+        let mut report = AnalysisReport { rulesets: serde_yaml::from_str(&yaml).unwrap() };
+        let before = report.clone();
+        let shared = |report: &AnalysisReport| {
+            let incidents = &report.rulesets[0].violations["chain-pom-001"].incidents;
+            incidents.windows(2).filter(|w| w[0].uri == w[1].uri).all(|w| Arc::ptr_eq(&w[0].uri, &w[1].uri))
+        };
+        assert!(!shared(&report));
+
+        let mut interner = Interner::default();
+        report.intern_with(&mut interner);
+        assert_eq!(report, before);
+        assert!(shared(&report));
+
+        let pooled = interner.len();
+        let mut again = before.clone();
+        again.intern_with(&mut interner);
+        assert_eq!(interner.len(), pooled);
+        assert!(Arc::ptr_eq(&again.rulesets[0].name, &report.rulesets[0].name));
+    }
+}
@@ -0,0 +1,27 @@
+pub mod burndown;
+pub mod cache;
+pub mod catalog;
+pub mod config;
+pub mod diff;
+pub mod export;
+pub mod fingerprint;
+pub mod fixes;
+pub mod grouping;
+pub mod input;
+pub mod intern;
+pub mod location;
+pub mod markdown;
+pub mod parallel;
+pub mod portfolio;
+pub mod prompt;
+pub mod related;
+pub mod server;
+pub mod selector;
+pub mod snippet;
+pub mod storage;
+pub mod suppression;
+pub mod triage;
+pub mod tui;
+pub mod validation;
+pub mod watch;
+pub mod yaml_parser;
@@ -0,0 +1,192 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::Arc;
+use crate::export::{common_root, relative_path, uri_path};
+use crate::yaml_parser::AnalysisReport;
+
+/// Editors that can be opened at a file and line from a URL.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Editor {
+    Vscode,
+    Idea,
+}
+
+impl Editor {
+    pub fn parse(name: &str) -> Option<Editor> {
+        match name {
+            "vscode" => Some(Editor::Vscode),
+            "idea" => Some(Editor::Idea),
+            _ => None,
+        }
+    }
+
+    /// URL opening `path` at `line` and `column`, `path` being absolute.
+    pub fn url(&self, path: &str, line: i32, column: i32) -> String {
+        match self {
+            Editor::Vscode => format!("vscode://file{}:{}:{}", encode(path), line, column),
+            Editor::Idea => format!("idea://open?file={}&line={}&column={}", encode(path), line, column),
+        }
+    }
+}
+
+// Percent-encodes the characters that would end or break the path part of a URL.
+fn encode(path: &str) -> String {
+    let mut out = String::with_capacity(path.len());
+    for c in path.chars() {
+        match c {
+            ' ' | '"' | '#' | '%' | '&' | '?' | '<' | '>' => {
+                let _ = write!(out, "%{:02X}", c as u32);
+            }
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// Local path of an analyzer `uri`, using the longest matching prefix of
+/// `uri_prefixes`, which maps URI prefixes to local directories.
+///
+/// Prefixes match whole path segments, `file:///app` maps `file:///app/pom.xml`
+/// but not `file:///application/pom.xml`.
+pub fn local_path(uri_prefixes: &BTreeMap<String, String>, uri: &str) -> Option<String> {
+    let (rest, dir) = uri_prefixes.iter()
+        .filter_map(|(prefix, dir)| {
+            let rest = uri.strip_prefix(prefix.as_str())?;
+            (prefix.ends_with('/') || rest.is_empty() || rest.starts_with('/')).then_some((rest, dir))
+        })
+        .min_by_key(|(rest, _)| rest.len())?;
+    Some(join_path(dir, rest))
+}
+
+// Joins `dir` and `rest` with exactly one separator, whatever either side ends or starts with.
+fn join_path(dir: &str, rest: &str) -> String {
+    match rest.trim_start_matches('/') {
+        "" => dir.to_string(),
+        rest => format!("{}/{}", dir.trim_end_matches('/'), rest),
+    }
+}
+
+/// Options of `locations`.
+#[derive(Clone, Debug, Default)]
+pub struct LocationOptions {
+    /// Root that paths are shown relative to, the common root of the report by default.
+    pub root: Option<String>,
+    /// Local checkout the relative paths are resolved against for editor URLs.
+    /// The analyzer usually runs in a container, so its paths rarely exist locally.
+    pub checkout: Option<String>,
+    /// URI prefixes mapped to local directories, used for editor URLs when
+    /// no checkout is given, see `local_path`.
+    pub uri_prefixes: BTreeMap<String, String>,
+    pub editor: Option<Editor>,
+}
+
+/// Position of an incident, in the `path:line:col` form compilers use.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Location {
+    pub path: String,
+    pub line: i32,
+    /// The analyzer does not report columns, incidents point at the start of the line.
+    pub column: i32,
+    pub rule_id: Arc<str>,
+    pub message: String,
+    /// `Violation::fingerprints`, taken relative to the `root` option when given.
+    pub fingerprint: String,
+    pub url: Option<String>,
+}
+
+impl std::fmt::Display for Location {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let message = self.message.lines().map(str::trim).find(|l| !l.is_empty()).unwrap_or_default();
+        write!(f, "{}:{}:{}: {}: {} [{}]", self.path, self.line, self.column, self.rule_id, message, self.fingerprint)?;
+        if let Some(url) = &self.url {
+            write!(f, " {}", url)?;
+        }
+        Ok(())
+    }
+}
+
+/// Lists every incident of `report` as a location, sorted by path and line.
+///
+/// Incidents without a line number point at line 1. Fingerprints take the
+/// `root` option as source root, never the common root, see `Violation::fingerprints`.
+pub fn locations(report: &AnalysisReport, options: &LocationOptions) -> Vec<Location> {
+    let root = options.root.clone().unwrap_or_else(|| common_root(report));
+    let mut locations = Vec::new();
+    for ruleset in &report.rulesets {
+        for (rule_id, violation) in &ruleset.violations {
+            let fingerprints = violation.fingerprints(rule_id, options.root.as_deref());
+            for (incident, fingerprint) in violation.incidents.iter().zip(fingerprints) {
+                let path = relative_path(&incident.uri, &root);
+                let line = incident.line_number.filter(|line| *line > 0).unwrap_or(1);
+                let url = options.editor.map(|editor| {
+                    let absolute = match &options.checkout {
+                        Some(checkout) => join_path(checkout, &path),
+                        None => local_path(&options.uri_prefixes, &incident.uri)
+                            .unwrap_or_else(|| uri_path(&incident.uri).to_string()),
+                    };
+                    editor.url(&absolute, line, 1)
+                });
+                locations.push(Location {
+                    path,
+                    line,
+                    column: 1,
+                    rule_id: rule_id.clone(),
+                    message: incident.message.clone(),
+                    fingerprint,
+                    url,
+                });
+            }
+        }
+    }
+    locations.sort_by(|a, b| (&a.path, a.line, &a.rule_id).cmp(&(&b.path, b.line, &b.rule_id)));
+    locations
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::yaml_parser::parse_yaml;
+
+    #[test]
+    fn editor_urls() {
+        assert_eq!(Editor::Vscode.url("/home/me/app/src/App.java", 12, 1), "vscode://file/home/me/app/src/App.java:12:1");
+        assert_eq!(Editor::Idea.url("/home/me/my app/App.java", 3, 1), "idea://open?file=/home/me/my%20app/App.java&line=3&column=1");
+        assert_eq!(Editor::parse("idea"), Some(Editor::Idea));
+        assert_eq!(Editor::parse("emacs"), None);
+    }
+
+    #[test]
+    fn compiler_style_listing() {
+        let report = parse_yaml("samples/demo-output.yaml").unwrap();
+        let options = LocationOptions {
+            checkout: Some("/work/demo/".to_string()),
+            editor: Some(Editor::Vscode),
+            ..Default::default()
+        };
+        let locations = locations(&report, &options);
+        assert_eq!(locations.len(), report.summary().incidents);
+
+        let first = locations[0].to_string();
+        assert!(first.starts_with("builtin/inclusion_tests/dir-0/inclusion-test.json:1:1: builtin-inclusion-test-json: "));
+        assert!(first.ends_with(&format!(" [{}] vscode://file/work/demo/builtin/inclusion_tests/dir-0/inclusion-test.json:1:1", locations[0].fingerprint)));
+        let violation = &report.rulesets[0].violations["builtin-inclusion-test-json"];
+        let index = violation.incidents.iter().position(|i| i.uri.ends_with("dir-0/inclusion-test.json")).unwrap();
+        assert_eq!(locations[0].fingerprint, violation.fingerprints("builtin-inclusion-test-json", None)[index]);
+        assert!(locations.iter().all(|l| l.line > 0 && !l.to_string().contains('\n')));
+
+        // Paths outside of the root stay absolute and are still joined with a single separator.
+        let options = LocationOptions { root: Some("/elsewhere".to_string()), ..options };
+        assert!(super::locations(&report, &options).iter().all(|l| !l.url.as_ref().unwrap().contains("demo//")));
+    }
+
+    #[test]
+    fn mapped_directories_with_trailing_separators() {
+        let uri_prefixes: BTreeMap<String, String> = [
+            ("file:///opt/input/source/".to_string(), "/home/me/src/".to_string()),
+            ("file:///m2".to_string(), "/".to_string()),
+        ].into_iter().collect();
+        assert_eq!(local_path(&uri_prefixes, "file:///opt/input/source/pom.xml").as_deref(), Some("/home/me/src/pom.xml"));
+        assert_eq!(local_path(&uri_prefixes, "file:///opt/input/source/").as_deref(), Some("/home/me/src/"));
+        assert_eq!(local_path(&uri_prefixes, "file:///m2/org/x.jar").as_deref(), Some("/org/x.jar"));
+    }
+}
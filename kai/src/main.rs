@@ -1,8 +1,24 @@
-mod yaml_parser;
-use std::collections::HashMap;
+use std::collections::BTreeMap;
+use std::sync::{Arc, OnceLock};
 use std::time::Instant;
-use yaml_parser::parse_yaml;
-use yaml_parser::Ruleset;
+use kai::burndown::{format_unix_time, Burndown};
+use kai::catalog::RuleCatalog;
+use kai::config::{Config, OutputFormat};
+use kai::parallel::load_reports;
+use kai::grouping::{group_incidents, GroupBy};
+use kai::location::{locations, Editor};
+use kai::markdown::{render_markdown, MarkdownOptions};
+use kai::export::{incident_records, write_rows, Delimited};
+use kai::fixes::{FixExample, FixStore};
+use kai::portfolio::Portfolio;
+use kai::prompt::incident_prompt;
+use kai::server;
+use kai::storage::ReportStore;
+use kai::suppression::{SuppressionResult, Suppressions};
+use kai::tui;
+use kai::watch::{watch, WatchQuery};
+use kai::yaml_parser::parse_yaml;
+use kai::yaml_parser::{AnalysisReport, ImpactedFiles};
 
 #[allow(dead_code)]
 fn print_debug_demo_report() {
@@ -28,13 +44,608 @@ fn print_debug_demo_report() {
     }   
 }
 
-fn main() {
+// Prints the incidents removed by `suppressions` and warns about unused entries.
+fn print_suppressions(name: &str, result: &SuppressionResult) {
+    for suppressed in &result.suppressed {
+        eprintln!("{}: suppressed {} at {}:{} ({})",
+            name,
+            suppressed.rule_id,
+            suppressed.incident.uri,
+            suppressed.incident.line_number.unwrap_or_default(),
+            suppressed.suppression.justification);
+    }
+    for unused in &result.unused {
+        let target = unused.uri.as_deref().or(unused.fingerprint.as_deref()).unwrap_or_default();
+        eprintln!("{}: warning: unused suppression for {} on `{}`", name, unused.rule_id, target);
+    }
+}
 
-   //print_debug_demo_report();
+// Removes the suppressed incidents, reporting them on stderr.
+fn suppress(name: &str, report: AnalysisReport) -> AnalysisReport {
+    match suppressions() {
+        Some(suppressions) => {
+            let result = suppressions.apply(&report, config().source_root.as_deref());
+            print_suppressions(name, &result);
+            result.report
+        }
+        None => report,
+    }
+}
+
+static CONFIG: OnceLock<Config> = OnceLock::new();
+
+// Settings from kai.toml and the environment, loaded once in `main`.
+fn config() -> &'static Config {
+    CONFIG.get_or_init(Config::default)
+}
+
+static SUPPRESSIONS: OnceLock<Option<Suppressions>> = OnceLock::new();
+
+// The suppressions given with `--suppressions FILE`, before or after the
+// command, or else the configured ones, loaded once in `main`.
+fn suppressions() -> Option<&'static Suppressions> {
+    SUPPRESSIONS.get_or_init(|| None).as_ref()
+}
+
+// Parses a report, narrows it to the configured labels and paths and removes
+// the suppressed incidents.
+fn load_report(path: &str) -> Result<AnalysisReport, Box<dyn std::error::Error>> {
+    let result = config().load_suppressed(path, suppressions())?;
+    print_suppressions(path, &result);
+    Ok(result.report)
+}
+
+// The configured reports, for commands given none on the command line.
+fn configured_reports(usage: &str) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    if config().reports.is_empty() {
+        return Err(usage.into());
+    }
+    Ok(config().reports.clone())
+}
+
+// The first configured report, for commands that work on a single one.
+fn first_configured_report() -> Result<Option<AnalysisReport>, Box<dyn std::error::Error>> {
+    config().reports.first().map(|arg| load_report(&named_report(arg).1)).transpose()
+}
+
+fn fixes_db() -> String {
+    config().fixes_db.clone().unwrap_or_else(|| "kai-fixes.db".to_string())
+}
+
+// Splits a `[name=]report.yaml` argument, defaulting the name to the file stem.
+fn named_report(arg: &str) -> (String, String) {
+    match arg.split_once('=') {
+        Some((name, path)) => (name.to_string(), path.to_string()),
+        None => {
+            let stem = std::path::Path::new(arg).file_stem().and_then(|s| s.to_str()).unwrap_or(arg);
+            (stem.to_string(), arg.to_string())
+        }
+    }
+}
+
+// Usage: kai serve [--addr 127.0.0.1:8080] [name=]report.yaml...
+async fn run_server(args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let mut addr = "127.0.0.1:8080".to_string();
+    let mut reports = BTreeMap::new();
+    let mut paths = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if arg == "--addr" {
+            addr = args.next().ok_or("--addr expects a value")?.clone();
+            continue;
+        }
+        paths.push(arg.clone());
+    }
+    if paths.is_empty() {
+        paths = configured_reports("serve expects at least one report")?;
+    }
+    for arg in &paths {
+        let (name, path) = named_report(arg);
+        reports.insert(name, load_report(&path)?);
+    }
+    server::serve(&addr, Arc::new(reports), config().source_root.as_deref()).await
+}
+
+// Usage: kai tui report.yaml
+fn run_tui(args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let path = args.first().cloned()
+        .or_else(|| config().reports.first().map(|arg| named_report(arg).1))
+        .ok_or("Usage: kai tui <report.yaml>")?;
+    let report = load_report(&path)?;
+    tui::run(&path, &report, config())
+}
+
+// Usage: kai validate [--json] report.yaml
+//
+// Exits with status 1 when the report has findings, so it can gate imports, and
+// with status 2 when it cannot be read at all.
+fn run_validate(args: &[String]) -> Result<bool, Box<dyn std::error::Error>> {
+    let json = args.iter().any(|arg| arg == "--json") || config().output.format == OutputFormat::Json;
+    let path = args.iter().find(|arg| *arg != "--json").ok_or("Usage: kai validate [--json] <report.yaml>")?;
+    // Findings are about the whole report, so configured filters do not apply.
+    let findings = config().open_report(path)?.validate();
+    if json {
+        println!("{}", serde_json::to_string_pretty(&findings)?);
+    } else {
+        for finding in &findings {
+            println!("{}", finding);
+        }
+        eprintln!("{}: {} finding(s)", path, findings.len());
+    }
+    Ok(findings.is_empty())
+}
+
+// Usage: kai catalog [--json] [app=]report.yaml...
+fn run_catalog(args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let json = args.iter().any(|arg| arg == "--json") || config().output.format == OutputFormat::Json;
+    let mut operands: Vec<String> = args.iter().filter(|arg| *arg != "--json").cloned().collect();
+    if operands.is_empty() {
+        operands = configured_reports("Usage: kai catalog [--json] [app=]<report.yaml>...")?;
+    }
+    let (names, paths): (Vec<String>, Vec<String>) = operands.iter().map(|arg| named_report(arg)).unzip();
+    let reports: Vec<AnalysisReport> = load_reports(&paths)?.iter()
+        .zip(&names)
+        .map(|(report, name)| Ok(suppress(name, config().select(report)?)))
+        .collect::<Result<_, Box<dyn std::error::Error>>>()?;
+    let catalog = RuleCatalog::from_reports(names.iter().map(String::as_str).zip(&reports));
+    if json {
+        println!("{}", serde_json::to_string_pretty(&catalog)?);
+    } else {
+        for rule in catalog.most_common() {
+            println!("{:>4} app(s) {:>6} incident(s)  {} ({})", rule.applications.len(), rule.incidents, rule.rule_id, rule.ruleset);
+        }
+    }
+    Ok(())
+}
+
+// Usage: kai portfolio <dir> [--json | --csv comparison|common|readiness]
+fn run_portfolio(args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let usage = "Usage: kai portfolio <dir> [--json | --csv comparison|common|readiness]";
+    let dir = args.first().ok_or(usage)?;
+    let mut portfolio = Portfolio::load_dir(dir, config())?;
+    for skipped in &portfolio.skipped {
+        eprintln!("{}: warning: skipped, not an analysis output: {}", skipped.source, skipped.error);
+    }
+    for application in &mut portfolio.applications {
+        application.report = suppress(&application.name, config().select(&application.report)?);
+    }
+    match args.get(1).map(String::as_str) {
+        Some("--json") => println!("{}", serde_json::to_string_pretty(&portfolio.report())?),
+        Some("--csv") => match args.get(2).map(String::as_str) {
+            Some("comparison") => write_rows(&portfolio.comparison(), Delimited::Csv, std::io::stdout())?,
+            Some("common") => write_rows(&portfolio.common_rules(2), Delimited::Csv, std::io::stdout())?,
+            Some("readiness") => write_rows(&portfolio.readiness(), Delimited::Csv, std::io::stdout())?,
+            _ => return Err(usage.into()),
+        },
+        Some(_) => return Err(usage.into()),
+        None => {
+            for readiness in portfolio.readiness() {
+                println!("{:>3}. {:<30} mandatory effort {:>6}  mandatory incidents {:>6}  effort {:>6}",
+                    readiness.rank, readiness.application, readiness.mandatory_effort, readiness.mandatory_incidents, readiness.effort);
+            }
+        }
+    }
+    Ok(())
+}
 
-   match parse_yaml("samples/coolstore_analysis_output.yaml") {
+// Usage: kai export [--tsv | --json] [--root DIR] [--label LABEL] [app=]report.yaml...
+//
+// Writes one row per incident. Options apply to the reports that follow them and
+// filters are applied to each report before it is flattened.
+fn run_export(args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let mut format = config().output.format;
+    let mut root = config().source_root.clone();
+    let mut label = None;
+    let mut records = Vec::new();
+    let mut given = false;
+    let export = |arg: &str, label: &Option<String>, root: &Option<String>| -> Result<Vec<_>, Box<dyn std::error::Error>> {
+        let (name, path) = named_report(arg);
+        let mut report = load_report(&path)?;
+        if let Some(label) = label {
+            report = report.filter_by_label(label);
+        }
+        Ok(incident_records(&name, &report, root.as_deref()))
+    };
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--tsv" => format = OutputFormat::Tsv,
+            "--json" => format = OutputFormat::Json,
+            "--root" => root = Some(args.next().ok_or("--root expects a value")?.clone()),
+            "--label" => label = Some(args.next().ok_or("--label expects a value")?.clone()),
+            _ => {
+                records.extend(export(arg, &label, &root)?);
+                given = true;
+            }
+        }
+    }
+    if !given {
+        for arg in &config().reports {
+            records.extend(export(arg, &label, &root)?);
+        }
+    }
+    match format {
+        OutputFormat::Json => {
+            println!("{}", serde_json::to_string_pretty(&records)?);
+            Ok(())
+        }
+        OutputFormat::Tsv => write_rows(&records, Delimited::Tsv, std::io::stdout()),
+        OutputFormat::Csv | OutputFormat::Text => write_rows(&records, Delimited::Csv, std::io::stdout()),
+    }
+}
+
+// Usage: kai scope [--source TECH]... [--target TECH]... [--json] report.yaml
+//
+// Lists the rulesets with violations on the migration path, and those left out.
+// Sources and targets default to the configured ones.
+fn run_scope(args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let mut path = config().migration_path();
+    let mut chosen = (false, false);
+    let mut json = config().output.format == OutputFormat::Json;
+    let mut report = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--source" | "--target" => {
+                let value = args.next().ok_or(format!("{} expects a value", arg))?;
+                let (choices, replaced) = if arg == "--source" { (&mut path.sources, &mut chosen.0) } else { (&mut path.targets, &mut chosen.1) };
+                // Choices on the command line replace the configured ones.
+                if !*replaced {
+                    choices.clear();
+                    *replaced = true;
+                }
+                choices.extend(value.split(',').map(|choice| choice.trim().to_string()).filter(|choice| !choice.is_empty()));
+            }
+            "--json" => json = true,
+            path => report = Some(suppress(path, config().open_report(path)?)),
+        }
+    }
+    let report = match report {
+        Some(report) => report,
+        None => config().reports.first()
+            .map(|arg| {
+                let (name, path) = named_report(arg);
+                Ok::<_, Box<dyn std::error::Error>>(suppress(&name, config().open_report(&path)?))
+            })
+            .transpose()?
+            .ok_or("Usage: kai scope [--source TECH]... [--target TECH]... [--json] <report.yaml>")?,
+    };
+    let scoped = report.scope(&path);
+    if json {
+        println!("{}", serde_json::to_string_pretty(&scoped)?);
+    } else {
+        for contribution in &scoped.contributed {
+            println!("{:>4} violation(s) {:>6} incident(s) effort {:>6}  {}",
+                contribution.violations, contribution.incidents, contribution.effort, contribution.ruleset);
+        }
+        for ruleset in &scoped.excluded {
+            println!("excluded  {}", ruleset);
+        }
+    }
+    Ok(())
+}
+
+// Usage: kai markdown [--baseline old.yaml] [--max-bytes N] [--root DIR] report.yaml
+//
+// Suppressions apply to the baseline too, so suppressed incidents are neither new nor resolved.
+fn run_markdown(args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let mut options = MarkdownOptions { root: config().source_root.clone(), ..Default::default() };
+    let mut baseline = None;
+    let mut report = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--baseline" => baseline = Some(load_report(args.next().ok_or("--baseline expects a value")?)?),
+            "--max-bytes" => options.max_bytes = args.next().ok_or("--max-bytes expects a value")?.parse()?,
+            "--root" => options.root = Some(args.next().ok_or("--root expects a value")?.clone()),
+            path => report = Some(load_report(path)?),
+        }
+    }
+    if report.is_none() {
+        report = first_configured_report()?;
+    }
+    let report = report.ok_or("Usage: kai markdown [--baseline old.yaml] [--max-bytes N] [--root DIR] <report.yaml>")?;
+    print!("{}", render_markdown(&report, baseline.as_ref(), &options));
+    Ok(())
+}
+
+// Usage: kai group [--by module,package,extension] [--root DIR] [--json] report.yaml
+fn run_group(args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let mut keys = vec![GroupBy::Module, GroupBy::Package];
+    let mut root = config().source_root.clone();
+    let mut json = config().output.format == OutputFormat::Json;
+    let mut report = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--by" => {
+                keys = args.next().ok_or("--by expects a value")?
+                    .split(',')
+                    .map(|key| GroupBy::parse(key).ok_or(format!("unknown grouping {}, expected module, package or extension", key)))
+                    .collect::<Result<_, _>>()?;
+            }
+            "--root" => root = Some(args.next().ok_or("--root expects a value")?.clone()),
+            "--json" => json = true,
+            path => report = Some(load_report(path)?),
+        }
+    }
+    if report.is_none() {
+        report = first_configured_report()?;
+    }
+    let report = report.ok_or("Usage: kai group [--by module,package,extension] [--root DIR] [--json] <report.yaml>")?;
+    let tree = group_incidents(&report, &keys, root.as_deref());
+    if json {
+        println!("{}", serde_json::to_string_pretty(&tree)?);
+    } else {
+        print!("{}", tree.render());
+    }
+    Ok(())
+}
+
+// Usage: kai locations [--root DIR] [--checkout DIR] [--editor vscode|idea] report.yaml...
+//
+// Options apply to the reports that follow them.
+fn run_locations(args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let mut options = config().location_options();
+    let mut given = false;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--root" => options.root = Some(args.next().ok_or("--root expects a value")?.clone()),
+            "--checkout" => options.checkout = Some(args.next().ok_or("--checkout expects a value")?.clone()),
+            "--editor" => {
+                let name = args.next().ok_or("--editor expects a value")?;
+                options.editor = Some(Editor::parse(name).ok_or(format!("unknown editor {}, expected vscode or idea", name))?);
+            }
+            path => {
+                let report = load_report(path)?;
+                for location in locations(&report, &options) {
+                    println!("{}", location);
+                }
+                given = true;
+            }
+        }
+    }
+    if !given {
+        for arg in &config().reports {
+            let report = load_report(&named_report(arg).1)?;
+            for location in locations(&report, &options) {
+                println!("{}", location);
+            }
+        }
+    }
+    Ok(())
+}
+
+// Usage: kai watch [--files | --label LABEL] [--delay-ms 500] report.yaml...
+fn run_watch(args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let mut query = WatchQuery::Summary;
+    let mut delay = std::time::Duration::from_millis(500);
+    let mut paths = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--files" => query = WatchQuery::Files,
+            "--label" => query = WatchQuery::Label(args.next().ok_or("--label expects a value")?.clone()),
+            "--delay-ms" => delay = std::time::Duration::from_millis(args.next().ok_or("--delay-ms expects a value")?.parse()?),
+            path => paths.push(path.to_string()),
+        }
+    }
+    if paths.is_empty() {
+        paths = configured_reports("Usage: kai watch [--files | --label LABEL] [--delay-ms 500] <report.yaml>...")?
+            .iter()
+            .map(|arg| named_report(arg).1)
+            .collect();
+    }
+    watch(&paths, &query, delay, config(), suppressions())
+}
+
+// Modification time of the file at `path`, as an ISO 8601 date-time.
+fn modified_at(path: &str) -> Result<String, Box<dyn std::error::Error>> {
+    let modified = std::fs::metadata(path)?.modified()?.duration_since(std::time::UNIX_EPOCH)?;
+    Ok(format_unix_time(modified.as_secs() as i64))
+}
+
+// Usage: kai import --store kai.db --app NAME [--at TIMESTAMP] report.yaml
+//
+// Stores the report as a new run of the application, dated with --at, an
+// ISO 8601 date or date-time, or else with the modification time of the file.
+fn run_import(args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let usage = "Usage: kai import --store kai.db --app NAME [--at TIMESTAMP] report.yaml";
+    let mut store = None;
+    let mut app = None;
+    let mut at = None;
+    let mut paths = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--store" => store = Some(args.next().ok_or("--store expects a value")?.clone()),
+            "--app" => app = Some(args.next().ok_or("--app expects a value")?.clone()),
+            "--at" => at = Some(args.next().ok_or("--at expects a value")?.clone()),
+            path => paths.push(path.to_string()),
+        }
+    }
+    let (Some(store), Some(app), [path]) = (store, app, paths.as_slice()) else {
+        return Err(usage.into());
+    };
+    let at = match at {
+        Some(at) => at,
+        None => modified_at(path)?,
+    };
+    let run_id = ReportStore::open(&store)?.import_report_at(&app, path, &at, &load_report(path)?)?;
+    eprintln!("imported {} as run {} of {} at {}", path, run_id, app, at);
+    Ok(())
+}
+
+// Usage: kai burndown [--csv | --csv-breakdown | --svg] [--width 60] [--store kai.db --app NAME] [when=]report.yaml...
+//
+// Each report is timestamped with `when`, an ISO 8601 date, or else with the
+// modification time of the file. With --store the runs stored by `kai import`
+// for --app are used instead of report files, and giving both is an error.
+fn run_burndown(args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let usage = "Usage: kai burndown [--csv | --csv-breakdown | --svg] [--width 60] [--store kai.db --app NAME] [when=]report.yaml...";
+    let mut output = "ascii";
+    let mut width: usize = 60;
+    let mut store = None;
+    let mut app = None;
+    let mut burndown = Burndown::default();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--csv" => output = "csv",
+            "--csv-breakdown" => output = "csv-breakdown",
+            "--svg" => output = "svg",
+            "--width" => width = args.next().ok_or("--width expects a value")?.parse()?,
+            "--store" => store = Some(args.next().ok_or("--store expects a value")?.clone()),
+            "--app" => app = Some(args.next().ok_or("--app expects a value")?.clone()),
+            _ => {
+                let (when, path) = match arg.split_once('=') {
+                    Some((when, path)) => (when.to_string(), path.to_string()),
+                    None => (modified_at(arg)?, arg.clone()),
+                };
+                burndown.add(&when, &load_report(&path)?);
+            }
+        }
+    }
+    match (store, app) {
+        (Some(_), Some(_)) if !burndown.points().is_empty() => return Err("--store takes no report operands".into()),
+        (Some(store), Some(app)) => burndown = Burndown::from_store(&ReportStore::open(&store)?, &app)?,
+        (None, None) => {}
+        _ => return Err("--store and --app go together".into()),
+    }
+    if burndown.points().is_empty() {
+        return Err(usage.into());
+    }
+    match output {
+        "csv" => write_rows(burndown.points(), Delimited::Csv, std::io::stdout())?,
+        "csv-breakdown" => write_rows(&burndown.breakdown(), Delimited::Csv, std::io::stdout())?,
+        "svg" => print!("{}", burndown.svg(640, 320)),
+        _ => print!("{}", burndown.ascii(width)),
+    }
+    Ok(())
+}
+
+// Usage: kai fixes add [--db kai-fixes.db] --rule RULE_ID [--explanation TEXT] patch.diff...
+//        kai fixes list [--db kai-fixes.db] [--rule RULE_ID]
+//        kai fixes remove [--db kai-fixes.db] ID...
+//
+// Every hunk of an accepted patch is stored as one example fix of the rule.
+fn run_fixes(args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let usage = "Usage: kai fixes add|list|remove [--db kai-fixes.db] [--rule RULE_ID] [--explanation TEXT] [patch.diff | ID]...";
+    let (command, args) = args.split_first().ok_or(usage)?;
+    let mut db = fixes_db();
+    let mut rule = None;
+    let mut explanation = String::new();
+    let mut operands = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--db" => db = args.next().ok_or("--db expects a value")?.clone(),
+            "--rule" => rule = Some(args.next().ok_or("--rule expects a value")?.clone()),
+            "--explanation" => explanation = args.next().ok_or("--explanation expects a value")?.clone(),
+            _ => operands.push(arg.clone()),
+        }
+    }
+    let mut store = FixStore::open(&db)?;
+    match command.as_str() {
+        "add" => {
+            let rule = rule.ok_or("--rule is required to add fixes")?;
+            for path in &operands {
+                let patch = std::fs::read_to_string(path)?;
+                for example in FixExample::from_patch(&rule, &patch, &explanation) {
+                    let id = store.add(&example)?;
+                    println!("{}: added fix {} for {} in {}", path, id, rule, example.file);
+                }
+            }
+        }
+        "list" => {
+            for fix in store.fixes(rule.as_deref())? {
+                let summary = fix.explanation.lines().next().unwrap_or_default();
+                println!("{:>5}  {}  {}  {}", fix.id, fix.rule_id, fix.file, summary);
+            }
+        }
+        "remove" => {
+            for id in &operands {
+                store.remove(id.parse()?)?;
+            }
+        }
+        _ => return Err(usage.into()),
+    }
+    Ok(())
+}
+
+// Usage: kai prompt [--db kai-fixes.db] [--examples 3] [--context 10] [--related 5] [--request] report.yaml RULE_ID [INCIDENT]
+//
+// Prints the prompt for the INCIDENT-th incident of the rule, counting from 0,
+// with the most similar past fixes of the rule as examples and the impacted
+// files related to the incident as context. With --request it is wrapped in a
+// chat completion request for the configured model, ready to be posted to
+// the URL printed on stderr.
+fn run_prompt(args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let usage = "Usage: kai prompt [--db kai-fixes.db] [--examples 3] [--context 10] [--related 5] [--request] report.yaml RULE_ID [INCIDENT]";
+    let mut db = fixes_db();
+    let mut options = config().prompt_options();
+    let mut request = false;
+    let mut operands = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--db" => db = args.next().ok_or("--db expects a value")?.clone(),
+            "--examples" => options.examples = args.next().ok_or("--examples expects a value")?.parse()?,
+            "--context" => options.context = args.next().ok_or("--context expects a value")?.parse()?,
+            "--related" => options.related = args.next().ok_or("--related expects a value")?.parse()?,
+            "--request" => request = true,
+            _ => operands.push(arg.as_str()),
+        }
+    }
+    let (path, rule_id, index) = match operands[..] {
+        [path, rule_id] => (path, rule_id, 0),
+        [path, rule_id, index] => (path, rule_id, index.parse()?),
+        _ => return Err(usage.into()),
+    };
+    let report = load_report(path)?;
+    let violation = report.rulesets.iter()
+        .find_map(|ruleset| ruleset.violations.get(rule_id))
+        .ok_or_else(|| format!("{}: no violation of {}", path, rule_id))?;
+    let incident = violation.incidents.get(index)
+        .ok_or_else(|| format!("{} has {} incidents", rule_id, violation.incidents.len()))?;
+    let prompt = incident_prompt(&FixStore::open(&db)?, &report, rule_id, violation, incident, &options)?;
+    if request {
+        let request = config().chat_request(prompt)?;
+        eprintln!("POST {}", config().chat_url());
+        println!("{}", serde_json::to_string_pretty(&request)?);
+    } else {
+        print!("{}", prompt);
+    }
+    Ok(())
+}
+
+// Usage: kai config [--paths]
+//
+// Prints the effective configuration, after merging the configuration files
+// and the environment, or with --paths the files it was read from. Works with
+// an invalid configuration too, printing its problems or the files.
+fn run_config(args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    match args.first().map(String::as_str) {
+        Some("--paths") => {
+            let env = |name: &str| std::env::var(name).ok();
+            for path in Config::layer_paths(&env, &std::env::current_dir()?) {
+                println!("{}", path.display());
+            }
+        }
+        Some(_) => return Err("Usage: kai config [--paths]".into()),
+        None => {
+            let config = Config::load().map_err(|e| format!("invalid configuration\n{}", e))?;
+            print!("{}", toml::to_string(&config)?);
+        }
+    }
+    Ok(())
+}
+
+fn run_default() {
+   match load_report("samples/coolstore_analysis_output.yaml") {
     Ok(report) => {
-        let impacted_files: HashMap<String, HashMap<String, Ruleset>> = report.impacted_files()
+        let impacted_files: ImpactedFiles = report.impacted_files()
             .into_iter()
             .filter(|(uri, _impacted_ruleset)| {
                 !uri.starts_with("file:///root/.m2")
@@ -43,7 +654,76 @@ fn main() {
         println!("Parsed report has {:?} impacted files", impacted_files.len())
     },
     Err(e) => eprintln!("Error parsing YAML: {}", e),
+   }
 }
 
+#[tokio::main]
+async fn main() {
+
+   //print_debug_demo_report();
+
+   let mut args: Vec<String> = std::env::args().collect();
+   // `--suppressions FILE` applies to every command, wherever it is given.
+   let suppressions_arg = match args.iter().position(|arg| arg == "--suppressions") {
+       Some(index) if index + 1 < args.len() => args.drain(index..index + 2).nth(1),
+       Some(_) => {
+           eprintln!("Error: --suppressions expects a value");
+           std::process::exit(1);
+       }
+       None => None,
+   };
+   let command = args.get(1).map(String::as_str);
+   match Config::load() {
+       Ok(loaded) => { let _ = CONFIG.set(loaded); }
+       // `kai config` reports the problems itself and can still list the files.
+       Err(_) if command == Some("config") => {}
+       Err(e) => {
+           eprintln!("Error: invalid configuration\n{}", e);
+           std::process::exit(1);
+       }
+   }
+   if let Some(path) = suppressions_arg.or_else(|| config().suppressions.clone()) {
+       match config().load_suppression_file(&path) {
+           Ok(loaded) => { let _ = SUPPRESSIONS.set(Some(loaded)); }
+           Err(e) => {
+               eprintln!("Error: {}", e);
+               std::process::exit(1);
+           }
+       }
+   }
 
+   let command_args = args.get(2..).unwrap_or_default();
+   let result = match command {
+       Some("serve") => run_server(command_args).await,
+       Some("tui") => run_tui(command_args),
+       Some("validate") => match run_validate(command_args) {
+           Ok(true) => Ok(()),
+           Ok(false) => std::process::exit(1),
+           Err(e) => {
+               eprintln!("Error: {}", e);
+               std::process::exit(2);
+           }
+       },
+       Some("catalog") => run_catalog(command_args),
+       Some("portfolio") => run_portfolio(command_args),
+       Some("export") => run_export(command_args),
+       Some("scope") => run_scope(command_args),
+       Some("markdown") => run_markdown(command_args),
+       Some("group") => run_group(command_args),
+       Some("locations") => run_locations(command_args),
+       Some("watch") => run_watch(command_args),
+       Some("import") => run_import(command_args),
+       Some("burndown") => run_burndown(command_args),
+       Some("fixes") => run_fixes(command_args),
+       Some("prompt") => run_prompt(command_args),
+       Some("config") => run_config(command_args),
+       _ => {
+           run_default();
+           Ok(())
+       }
+   };
+   if let Err(e) = result {
+       eprintln!("Error: {}", e);
+       std::process::exit(1);
+   }
 }
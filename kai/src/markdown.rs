@@ -0,0 +1,243 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::Arc;
+use crate::diff::{diff_reports, DiffEntry};
+use crate::export::{common_root, relative_path};
+use crate::yaml_parser::{AnalysisReport, Ruleset};
+
+/// Options of `render_markdown`.
+#[derive(Clone, Debug)]
+pub struct MarkdownOptions {
+    /// Upper bound of the output size in bytes. GitHub rejects comments over 65536 characters.
+    pub max_bytes: usize,
+    /// Incident lines listed per violation before the rest is summarized.
+    pub max_incidents_per_violation: usize,
+    /// Root that file paths are shown relative to, the common root of the report by default.
+    /// Fingerprints are only taken relative to it when it is given.
+    pub root: Option<String>,
+}
+
+impl Default for MarkdownOptions {
+    fn default() -> Self {
+        MarkdownOptions {
+            max_bytes: 60_000,
+            max_incidents_per_violation: 10,
+            root: None,
+        }
+    }
+}
+
+// First line of `text`, shortened and made safe to embed in a list item.
+fn inline(text: &str) -> String {
+    let line = text.lines().map(str::trim).find(|l| !l.is_empty()).unwrap_or_default();
+    let mut short: String = line.chars().take(160).collect();
+    if short.len() < line.len() {
+        short.push('…');
+    }
+    short.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('|', "\\|")
+}
+
+// `url` percent-encoded where it would end a link destination early.
+fn link_url(url: &str) -> String {
+    url.trim()
+        .replace(' ', "%20")
+        .replace('(', "%28")
+        .replace(')', "%29")
+        .replace('<', "%3C")
+        .replace('>', "%3E")
+}
+
+fn totals(report: &AnalysisReport) -> String {
+    let mut rows = BTreeMap::<String, (usize, usize, i64)>::new();
+    for ruleset in &report.rulesets {
+        for violation in ruleset.violations.values() {
+            let incidents = violation.incidents.len();
+            let row = rows.entry(violation.category.clone().unwrap_or_else(|| "none".to_string())).or_default();
+            row.0 += 1;
+            row.1 += incidents;
+            row.2 += violation.effort.unwrap_or(0) as i64 * incidents as i64;
+        }
+    }
+    let summary = report.summary();
+    let mut out = String::from("| Category | Violations | Incidents | Effort |\n|---|---:|---:|---:|\n");
+    for (category, (violations, incidents, effort)) in rows {
+        let _ = writeln!(out, "| {} | {} | {} | {} |", category, violations, incidents, effort);
+    }
+    let _ = writeln!(out, "| **Total** | **{}** | **{}** | **{}** |", summary.violations, summary.incidents, summary.effort);
+    out
+}
+
+const DETAILS_END: &str = "\n</details>\n\n";
+
+// Lists at most five times `max_incidents_per_violation` entries, stopping
+// early once `budget` bytes are used, closing lines included.
+fn diff_list(title: &str, entries: &[DiffEntry], root: &str, options: &MarkdownOptions, budget: usize) -> String {
+    let limit = options.max_incidents_per_violation * 5;
+    let more = |count: usize| format!("- _… and {} more_\n", count);
+    let budget = budget.saturating_sub(more(entries.len()).len() + DETAILS_END.len());
+    let mut out = format!("<details>\n<summary>{} ({})</summary>\n\n", title, entries.len());
+    let mut shown = 0;
+    for entry in entries.iter().take(limit) {
+        let line = format!("- `{}` {}:{} `{}` {}\n",
+            entry.rule_id,
+            relative_path(&entry.incident.uri, root),
+            entry.incident.line_number.unwrap_or_default(),
+            entry.fingerprint,
+            inline(&entry.incident.message));
+        if out.len() + line.len() > budget {
+            break;
+        }
+        out.push_str(&line);
+        shown += 1;
+    }
+    if entries.len() > shown {
+        out.push_str(&more(entries.len() - shown));
+    }
+    out.push_str(DETAILS_END);
+    out
+}
+
+fn file_section(uri: &str, rulesets: &BTreeMap<Arc<str>, Ruleset>, root: &str, options: &MarkdownOptions) -> String {
+    let incidents: usize = rulesets.values()
+        .flat_map(|r| r.violations.values())
+        .map(|v| v.incidents.len())
+        .sum();
+    let mut out = format!("<details>\n<summary><code>{}</code> — {} incident(s)</summary>\n\n", relative_path(uri, root), incidents);
+    for ruleset in rulesets.values() {
+        for (rule_id, violation) in &ruleset.violations {
+            let _ = write!(out, "- **{}** ({}", rule_id, violation.category.as_deref().unwrap_or("none"));
+            if let Some(effort) = violation.effort {
+                let _ = write!(out, ", effort {}", effort);
+            }
+            out.push(')');
+            if !violation.description.trim().is_empty() {
+                let _ = write!(out, ": {}", inline(&violation.description));
+            }
+            for link in &violation.links {
+                let _ = write!(out, " [{}]({})", inline(&link.title), link_url(&link.url));
+            }
+            out.push('\n');
+            let fingerprints = violation.fingerprints(rule_id, options.root.as_deref());
+            for (incident, fingerprint) in violation.incidents.iter().zip(fingerprints).take(options.max_incidents_per_violation) {
+                let _ = writeln!(out, "  - line {} `{}`: {}",
+                    incident.line_number.unwrap_or_default(),
+                    fingerprint,
+                    inline(&incident.message));
+            }
+            if violation.incidents.len() > options.max_incidents_per_violation {
+                let _ = writeln!(out, "  - _… and {} more_", violation.incidents.len() - options.max_incidents_per_violation);
+            }
+        }
+    }
+    out.push_str(DETAILS_END);
+    out
+}
+
+// Appends `section` when it fits in `budget`. Once one does not, it and every
+// later section are counted in `omitted` instead, so the output keeps its order.
+fn push_section(out: &mut String, section: &str, budget: usize, omitted: &mut usize) {
+    if *omitted > 0 || out.len() + section.len() > budget {
+        *omitted += 1;
+    } else {
+        out.push_str(section);
+    }
+}
+
+/// Renders a Markdown summary of `report` meant to be pasted into a pull request.
+///
+/// With a `baseline`, a "Changed since baseline" section lists the new and
+/// resolved incidents. The output never exceeds `options.max_bytes`: sections
+/// are dropped from the end once the next one would not fit, and a note says
+/// how many were left out.
+pub fn render_markdown(report: &AnalysisReport, baseline: Option<&AnalysisReport>, options: &MarkdownOptions) -> String {
+    let root = options.root.clone().unwrap_or_else(|| common_root(report));
+    // Keep room for the truncation note.
+    let budget = options.max_bytes.saturating_sub(100);
+    let mut out = String::new();
+    let mut omitted = 0;
+    push_section(&mut out, &format!("## Migration analysis\n\n{}\n", totals(report)), budget, &mut omitted);
+
+    if let Some(baseline) = baseline {
+        let diff = diff_reports(baseline, report, options.root.as_deref());
+        let header = format!("### Changed since baseline\n\n{} new, {} resolved, {} unchanged incident(s)\n\n",
+            diff.new.len(), diff.resolved.len(), diff.unchanged);
+        push_section(&mut out, &header, budget, &mut omitted);
+        for (title, entries) in [("New incidents", &diff.new), ("Resolved incidents", &diff.resolved)] {
+            if !entries.is_empty() {
+                // Each list may use up to a third of what is left, the rest goes to the files.
+                let list_budget = budget.saturating_sub(out.len()) / 3;
+                push_section(&mut out, &diff_list(title, entries, &root, options, list_budget), budget, &mut omitted);
+            }
+        }
+    }
+
+    let impacted_files = report.impacted_files();
+    push_section(&mut out, &format!("### Files ({})\n\n", impacted_files.len()), budget, &mut omitted);
+    for (uri, rulesets) in &impacted_files {
+        push_section(&mut out, &file_section(uri, rulesets, &root, options), budget, &mut omitted);
+    }
+    if omitted > 0 {
+        let note = format!("_Output truncated: {} more section(s) not shown._\n", omitted);
+        if out.len() + note.len() <= options.max_bytes {
+            out.push_str(&note);
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::yaml_parser::{parse_yaml, Link};
+
+    #[test]
+    fn summary_with_files() {
+        let report = parse_yaml("samples/demo-output.yaml").unwrap();
+        let markdown = render_markdown(&report, None, &MarkdownOptions::default());
+        assert!(markdown.contains("| mandatory | "));
+        assert!(markdown.contains("| **Total** | **23** | **86** | **100** |"));
+        assert!(markdown.contains("<summary><code>customers-tomcat-legacy/pom.xml</code> — 34 incident(s)</summary>"));
+        assert!(markdown.contains("&lt;groupId&gt;"));
+        assert!(!markdown.contains("Changed since baseline"));
+        assert!(!markdown.contains("truncated"));
+    }
+
+    #[test]
+    fn link_urls_stay_in_the_link() {
+        assert_eq!(link_url("https://example.com/Java EE (legacy)"), "https://example.com/Java%20EE%20%28legacy%29");
+        let mut report = parse_yaml("samples/demo-output.yaml").unwrap();
+        let violation = report.rulesets[0].violations.values_mut().next().unwrap();
+        violation.links = vec![Link { url: "https://en.wikipedia.org/wiki/Jakarta_EE_(software)".to_string(), title: "Jakarta EE".to_string() }];
+        let markdown = render_markdown(&report, None, &MarkdownOptions::default());
+        assert!(markdown.contains("[Jakarta EE](https://en.wikipedia.org/wiki/Jakarta_EE_%28software%29)"));
+    }
+
+    #[test]
+    fn baseline_and_truncation() {
+        let baseline = parse_yaml("samples/demo-output.yaml").unwrap();
+        let mut report = baseline.clone();
+        report.rulesets[0].violations.remove("chain-pom-001");
+
+        let options = MarkdownOptions { max_bytes: 4_000, ..Default::default() };
+        let markdown = render_markdown(&report, Some(&baseline), &options);
+        assert!(markdown.contains("0 new, 25 resolved"));
+        assert!(markdown.contains("<summary>Resolved incidents (25)</summary>"));
+        assert!(markdown.len() <= options.max_bytes);
+        assert!(markdown.contains("more section(s) not shown._"));
+    }
+
+    #[test]
+    fn limit_below_the_header() {
+        let report = parse_yaml("samples/demo-output.yaml").unwrap();
+        let header = render_markdown(&report, None, &MarkdownOptions::default());
+        let header = &header[..header.find("### Files").unwrap()];
+
+        for max_bytes in [0, 20, 60, header.len() - 1] {
+            let options = MarkdownOptions { max_bytes, ..Default::default() };
+            let markdown = render_markdown(&report, Some(&report), &options);
+            assert!(markdown.len() <= max_bytes, "{} bytes over a limit of {}", markdown.len(), max_bytes);
+        }
+        let markdown = render_markdown(&report, None, &MarkdownOptions { max_bytes: 60, ..Default::default() });
+        assert!(markdown.starts_with("_Output truncated: ") && markdown.ends_with(" more section(s) not shown._\n"));
+    }
+}
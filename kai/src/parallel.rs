@@ -0,0 +1,111 @@
+use std::collections::btree_map::Entry;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use rayon::prelude::*;
+use crate::yaml_parser::{index_ruleset, parse_yaml, AnalysisReport, ImpactedFiles};
+
+/// Parses many report files concurrently.
+///
+/// The reports are returned in the order of `paths`. The first file that
+/// fails to parse aborts the whole load, with the path in the error message.
+pub fn load_reports(paths: &[String]) -> Result<Vec<AnalysisReport>, Box<dyn std::error::Error>> {
+    let reports = paths
+        .par_iter()
+        .map(|path| parse_yaml(path).map_err(|e| format!("{}: {}", path, e)))
+        .collect::<Result<Vec<AnalysisReport>, String>>()?;
+    Ok(reports)
+}
+
+// Merges the partial index `right` into `left`. Incidents of `right` are
+// appended after those of `left`, which keeps the sequential order as long
+// as the reduction itself is ordered.
+fn merge(
+    mut left: ImpactedFiles,
+    right: ImpactedFiles,
+) -> ImpactedFiles {
+    for (uri, rulesets) in right {
+        let impacted_rulesets = left.entry(uri).or_default();
+        for (ruleset_name, ruleset) in rulesets {
+            match impacted_rulesets.get_mut(&ruleset_name) {
+                Some(existing) => {
+                    for (violation_key, violation) in ruleset.violations {
+                        match existing.violations.entry(violation_key) {
+                            Entry::Occupied(mut entry) => entry.get_mut().incidents.extend(violation.incidents),
+                            Entry::Vacant(entry) => {
+                                entry.insert(violation);
+                            }
+                        }
+                    }
+                }
+                None => {
+                    impacted_rulesets.insert(ruleset_name, ruleset);
+                }
+            }
+        }
+    }
+    left
+}
+
+impl AnalysisReport {
+
+    /// Parallel counterpart of `impacted_files`.
+    ///
+    /// Each ruleset is indexed on its own and the partial indexes are merged
+    /// with an ordered reduction, so the result, including the order of the
+    /// incidents, is identical to the sequential version.
+    pub fn par_impacted_files(&self) -> ImpactedFiles {
+        self.rulesets
+            .par_iter()
+            .map(|ruleset| {
+                let mut impacted_files = BTreeMap::new();
+                index_ruleset(ruleset, &mut impacted_files);
+                impacted_files
+            })
+            .reduce(BTreeMap::new, merge)
+    }
+
+    /// Parallel counterpart of `impacted_file_names`, sorted so the output is stable.
+    pub fn par_impacted_file_names(&self) -> Vec<Arc<str>> {
+        let mut uris: Vec<Arc<str>> = self.rulesets
+            .par_iter()
+            .flat_map_iter(|ruleset| {
+                ruleset.violations
+                    .values()
+                    .flat_map(|violation| violation.incidents.iter().map(|incident| incident.uri.clone()))
+            })
+            .collect();
+        uris.par_sort_unstable();
+        uris.dedup();
+        uris
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parallel_index_matches_sequential() {
+        let report = parse_yaml("samples/coolstore_analysis_output.yaml").unwrap();
+        assert_eq!(report.par_impacted_files(), report.impacted_files());
+
+        let mut expected = report.impacted_file_names();
+        expected.sort();
+        assert_eq!(report.par_impacted_file_names(), expected);
+    }
+
+    #[test]
+    fn load_reports_keeps_input_order() {
+        let paths = vec![
+            "samples/coolstore_analysis_output.yaml".to_string(),
+            "samples/demo-output.yaml".to_string(),
+            "samples/coolstore_analysis_output.yaml".to_string(),
+        ];
+        let reports = load_reports(&paths).unwrap();
+        let sizes: Vec<usize> = reports.iter().map(|r| r.rulesets.len()).collect();
+        assert_eq!(sizes, vec![26, 1, 26]);
+
+        let error = load_reports(&["samples/missing.yaml".to_string()]).err().unwrap();
+        assert!(error.to_string().starts_with("samples/missing.yaml:"));
+    }
+}
@@ -0,0 +1,273 @@
+use std::fs;
+use rayon::prelude::*;
+use serde::Serialize;
+use crate::catalog::RuleCatalog;
+use crate::config::Config;
+use crate::triage::SIDECAR_SUFFIX;
+use crate::yaml_parser::AnalysisReport;
+
+// Extensions of the report files picked up by `load_dir`, each optionally
+// followed by `.gz` or `.zst`.
+const REPORT_EXTENSIONS: [&str; 3] = [".yaml", ".yml", ".json"];
+
+// Application name of a report file, the file name without its extensions,
+// or `None` when the file is not a report.
+fn report_name(file_name: &str) -> Option<&str> {
+    let name = file_name.strip_suffix(".gz").or_else(|| file_name.strip_suffix(".zst")).unwrap_or(file_name);
+    REPORT_EXTENSIONS.iter().find_map(|extension| name.strip_suffix(extension))
+}
+
+/// One application of the portfolio and its analysis report.
+#[derive(Clone, Debug)]
+pub struct Application {
+    pub name: String,
+    pub source: String,
+    pub report: AnalysisReport,
+}
+
+/// Effort and incident totals of one application.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct ApplicationSummary {
+    pub application: String,
+    pub impacted_files: usize,
+    pub incidents: usize,
+    pub effort: i64,
+    pub mandatory_violations: usize,
+    pub mandatory_incidents: usize,
+    pub mandatory_effort: i64,
+}
+
+/// A rule hit by more than one application.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct CommonRule {
+    pub rule_id: String,
+    pub ruleset: String,
+    pub category: Option<String>,
+    pub effort: Option<i32>,
+    pub application_count: usize,
+    /// Application names separated by `;`, so the row stays flat for CSV.
+    pub applications: String,
+    pub incidents: usize,
+}
+
+/// Position of an application in the migration readiness ranking.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Readiness {
+    pub rank: usize,
+    pub application: String,
+    pub mandatory_effort: i64,
+    pub mandatory_incidents: usize,
+    pub effort: i64,
+}
+
+/// A YAML file of the portfolio directory that did not parse as an analysis output.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct SkippedFile {
+    pub source: String,
+    pub error: String,
+}
+
+/// Every portfolio view, as exported with `--json`.
+#[derive(Clone, Debug, Serialize)]
+pub struct PortfolioReport {
+    pub comparison: Vec<ApplicationSummary>,
+    pub common_rules: Vec<CommonRule>,
+    pub readiness: Vec<Readiness>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub skipped: Vec<SkippedFile>,
+}
+
+/// Analysis reports of many applications.
+#[derive(Clone, Debug, Default)]
+pub struct Portfolio {
+    pub applications: Vec<Application>,
+    /// Files left out by `load_dir`, so they are not mistaken for an empty report.
+    pub skipped: Vec<SkippedFile>,
+}
+
+impl Portfolio {
+
+    /// Loads every analysis output found in `dir`, with `Config::open_report`.
+    ///
+    /// Both `<dir>/<app>.yaml` and `<dir>/<app>/output.yaml` are picked up,
+    /// the application is named after the file without its extensions or
+    /// after the directory. JSON reports and compressed ones, such as
+    /// `<app>.json` or `<app>.yaml.gz`, are picked up too.
+    /// Triage sidecars written next to the reports are ignored. Other files
+    /// that do not parse as an analysis output, such as suppression files or
+    /// broken reports, are listed in `skipped`.
+    pub fn load_dir(dir: &str, config: &Config) -> Result<Portfolio, Box<dyn std::error::Error>> {
+        let mut found = Vec::new();
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            let file_name = path.file_name().and_then(|s| s.to_str()).unwrap_or_default();
+            if path.is_dir() {
+                let output = path.join("output.yaml");
+                if output.is_file() {
+                    found.push((file_name.to_string(), output.to_string_lossy().to_string()));
+                }
+            } else if let Some(name) = report_name(file_name).filter(|_| !file_name.ends_with(SIDECAR_SUFFIX)) {
+                found.push((name.to_string(), path.to_string_lossy().to_string()));
+            }
+        }
+        found.sort();
+
+        let reports: Vec<_> = found.par_iter().map(|(_, path)| config.open_report(path).map_err(|e| e.to_string())).collect();
+        let mut portfolio = Portfolio::default();
+        for ((name, source), report) in found.into_iter().zip(reports) {
+            match report {
+                Ok(report) => portfolio.applications.push(Application { name, source, report }),
+                Err(error) => portfolio.skipped.push(SkippedFile { source, error }),
+            }
+        }
+        Ok(portfolio)
+    }
+
+    pub fn add(&mut self, name: &str, source: &str, report: AnalysisReport) {
+        self.applications.push(Application { name: name.to_string(), source: source.to_string(), report });
+    }
+
+    /// Totals per application, in portfolio order.
+    pub fn comparison(&self) -> Vec<ApplicationSummary> {
+        self.applications
+            .iter()
+            .map(|app| {
+                let summary = app.report.summary();
+                let mut row = ApplicationSummary {
+                    application: app.name.clone(),
+                    impacted_files: summary.impacted_files,
+                    incidents: summary.incidents,
+                    effort: summary.effort,
+                    ..Default::default()
+                };
+                for ruleset in &app.report.rulesets {
+                    for violation in ruleset.violations.values() {
+                        if violation.category.as_deref() == Some("mandatory") && !violation.incidents.is_empty() {
+                            row.mandatory_violations += 1;
+                            row.mandatory_incidents += violation.incidents.len();
+                            row.mandatory_effort += violation.effort.unwrap_or(0) as i64 * violation.incidents.len() as i64;
+                        }
+                    }
+                }
+                row
+            })
+            .collect()
+    }
+
+    /// Rules hit by at least `min_applications` applications, most common first.
+    pub fn common_rules(&self, min_applications: usize) -> Vec<CommonRule> {
+        let catalog = RuleCatalog::from_reports(self.applications.iter().map(|app| (app.name.as_str(), &app.report)));
+        catalog
+            .most_common()
+            .into_iter()
+            .filter(|rule| rule.applications.len() >= min_applications)
+            .map(|rule| CommonRule {
+                rule_id: rule.rule_id.to_string(),
+                ruleset: rule.ruleset.to_string(),
+                category: rule.category.clone(),
+                effort: rule.effort,
+                application_count: rule.applications.len(),
+                applications: rule.applications.iter().cloned().collect::<Vec<_>>().join(";"),
+                incidents: rule.incidents,
+            })
+            .collect()
+    }
+
+    /// Applications ranked from the most to the least ready to migrate.
+    ///
+    /// Mandatory effort is what blocks a migration, so it ranks first; ties
+    /// are broken by mandatory incidents, total effort and name.
+    pub fn readiness(&self) -> Vec<Readiness> {
+        let mut comparison = self.comparison();
+        comparison.sort_by(|a, b| {
+            (a.mandatory_effort, a.mandatory_incidents, a.effort, &a.application)
+                .cmp(&(b.mandatory_effort, b.mandatory_incidents, b.effort, &b.application))
+        });
+        comparison
+            .into_iter()
+            .enumerate()
+            .map(|(index, summary)| Readiness {
+                rank: index + 1,
+                application: summary.application,
+                mandatory_effort: summary.mandatory_effort,
+                mandatory_incidents: summary.mandatory_incidents,
+                effort: summary.effort,
+            })
+            .collect()
+    }
+
+    pub fn report(&self) -> PortfolioReport {
+        PortfolioReport {
+            comparison: self.comparison(),
+            common_rules: self.common_rules(2),
+            readiness: self.readiness(),
+            skipped: self.skipped.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::{write_rows, Delimited};
+    use crate::triage::{Triage, TriageStatus};
+    use crate::yaml_parser::parse_yaml;
+
+    #[test]
+    fn load_dir_and_compare() {
+        let dir = std::env::temp_dir().join(format!("kai-portfolio-{}", std::process::id()));
+        fs::create_dir_all(dir.join("coolstore")).unwrap();
+        fs::copy("samples/coolstore_analysis_output.yaml", dir.join("coolstore").join("output.yaml")).unwrap();
+        fs::copy("samples/demo-output.yaml", dir.join("demo.yaml")).unwrap();
+        let mut gzip = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        std::io::Write::write_all(&mut gzip, &fs::read("samples/demo-output.yaml").unwrap()).unwrap();
+        fs::write(dir.join("demo.v2.yaml.gz"), gzip.finish().unwrap()).unwrap();
+        for version in ["app.v1", "app.v2"] {
+            fs::create_dir_all(dir.join(version)).unwrap();
+            fs::copy("samples/demo-output.yaml", dir.join(version).join("output.yaml")).unwrap();
+        }
+        fs::write(dir.join("notes.txt"), "ignored").unwrap();
+        fs::write(dir.join("suppressions.yaml"), "suppressions:\n- rule_id: xml-pom-001\n  uri: \"**/pom.xml\"\n").unwrap();
+        let mut triage = Triage::default();
+        let report = parse_yaml("samples/demo-output.yaml").unwrap();
+        let incident = &report.rulesets[0].violations["xml-pom-001"].incidents[0];
+        triage.set_status("xml-pom-001", incident, None, Some(TriageStatus::WontFix));
+        triage.save(&Triage::sidecar_path(dir.join("demo.yaml").to_str().unwrap())).unwrap();
+
+        let portfolio = Portfolio::load_dir(dir.to_str().unwrap(), &Config::default()).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+        let names: Vec<&str> = portfolio.applications.iter().map(|app| app.name.as_str()).collect();
+        assert_eq!(names, vec!["app.v1", "app.v2", "coolstore", "demo", "demo.v2"]);
+        let mut portfolio = portfolio;
+        portfolio.applications.retain(|app| app.name == "coolstore" || app.name == "demo");
+        let skipped: Vec<&str> = portfolio.skipped.iter().map(|file| file.source.as_str()).collect();
+        assert_eq!(skipped, vec![dir.join("suppressions.yaml").to_str().unwrap()]);
+
+        let comparison = portfolio.comparison();
+        assert_eq!(comparison[1].application, "demo");
+        assert_eq!(comparison[1].impacted_files, 20);
+        assert_eq!(comparison[1].mandatory_incidents, 4);
+        assert!(comparison[0].mandatory_violations > 0);
+
+        let common = portfolio.common_rules(2);
+        assert_eq!(common.len(), 1);
+        assert_eq!(common[0].applications, "coolstore;demo");
+
+        let readiness = portfolio.readiness();
+        assert_eq!(readiness[0].application, "demo");
+        assert_eq!(readiness.iter().map(|r| r.rank).collect::<Vec<_>>(), vec![1, 2]);
+    }
+
+    #[test]
+    fn csv_export() {
+        let mut portfolio = Portfolio::default();
+        portfolio.add("demo", "samples/demo-output.yaml", parse_yaml("samples/demo-output.yaml").unwrap());
+
+        let mut out = Vec::new();
+        write_rows(&portfolio.comparison(), Delimited::Csv, &mut out).unwrap();
+        let csv = String::from_utf8(out).unwrap();
+        let mut lines = csv.lines();
+        assert_eq!(lines.next(), Some("application,impacted_files,incidents,effort,mandatory_violations,mandatory_incidents,mandatory_effort"));
+        assert!(lines.next().unwrap().starts_with("demo,20,86,100,"));
+    }
+}
@@ -0,0 +1,152 @@
+use std::fmt::Write;
+use serde::Serialize;
+use crate::fixes::{incident_code, FixStore, RankedFix};
+use crate::related::RelatedFile;
+use crate::yaml_parser::{AnalysisReport, Incident, Violation};
+
+/// How much of the knowledge base and of the source goes into a prompt.
+#[derive(Clone, Debug)]
+pub struct PromptOptions {
+    /// Past fixes included as examples, at most.
+    pub examples: usize,
+    /// Snippet lines kept on each side of the flagged line.
+    pub context: usize,
+    /// Related impacted files listed as context, at most.
+    pub related: usize,
+}
+
+impl Default for PromptOptions {
+    fn default() -> Self {
+        PromptOptions { examples: 3, context: 10, related: 5 }
+    }
+}
+
+/// A chat completion request, in the OpenAI format that most providers and
+/// local model servers accept.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct ChatRequest {
+    pub model: String,
+    pub messages: Vec<ChatMessage>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_tokens: Option<u32>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct ChatMessage {
+    pub role: String,
+    pub content: String,
+}
+
+// Fence language of a file, as far as markdown renderers care.
+fn language(path: &str) -> &str {
+    match path.rsplit_once('.').map(|(_, extension)| extension) {
+        Some("java") => "java",
+        Some("xml") | Some("xhtml") => "xml",
+        Some("properties") => "properties",
+        Some("go") | Some("mod") => "go",
+        Some("yaml") | Some("yml") => "yaml",
+        Some("json") => "json",
+        _ => "",
+    }
+}
+
+// `code` in a fence, which needs its closing line even when the stored code
+// does not end with a newline.
+fn fenced(lang: &str, code: &str) -> String {
+    let newline = if code.is_empty() || code.ends_with('\n') { "" } else { "\n" };
+    format!("```{}\n{}{}```", lang, code, newline)
+}
+
+/// Builds the prompt asking to fix `incident`, with `fixes` as few-shot
+/// examples and `related` files that may have to change along with it.
+pub fn build_prompt(rule_id: &str, violation: &Violation, incident: &Incident, fixes: &[RankedFix], related: &[RelatedFile], options: &PromptOptions) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "# Migration issue\n");
+    let _ = writeln!(out, "Rule: {}", rule_id);
+    if let Some(category) = &violation.category {
+        let _ = writeln!(out, "Category: {}", category);
+    }
+    let _ = writeln!(out, "Description: {}", violation.description.trim());
+    let line = incident.line_number.map(|line| format!(":{}", line)).unwrap_or_default();
+    let _ = writeln!(out, "File: {}{}", incident.uri, line);
+    let _ = writeln!(out, "\n{}", incident.message.trim());
+
+    if !fixes.is_empty() {
+        let _ = writeln!(out, "\n# Past fixes for this rule");
+        for (n, ranked) in fixes.iter().enumerate() {
+            let fix = &ranked.fix;
+            let lang = language(&fix.file);
+            let _ = writeln!(out, "\n## Example {}\n", n + 1);
+            if !fix.explanation.is_empty() {
+                let _ = writeln!(out, "{}\n", fix.explanation.trim());
+            }
+            let _ = writeln!(out, "Before:\n{}\n", fenced(lang, &fix.before));
+            let _ = writeln!(out, "After:\n{}", fenced(lang, &fix.after));
+        }
+    }
+
+    if !related.is_empty() {
+        let _ = writeln!(out, "\n# Related files\n");
+        for file in related.iter().take(options.related) {
+            let reasons: Vec<String> = file.relations.iter().map(ToString::to_string).collect();
+            let _ = writeln!(out, "- {} ({})", file.uri, reasons.join(", "));
+        }
+    }
+
+    let _ = writeln!(out, "\n# Code to fix\n");
+    let _ = writeln!(out, "{}\n", fenced(language(&incident.uri), &incident_code(incident, options.context)));
+    let _ = writeln!(out, "Reply with the fixed code and a short explanation of the change.");
+    out
+}
+
+/// Same as `build_prompt`, with the examples retrieved from `store` and the
+/// files related to the incident in `report`.
+pub fn incident_prompt(store: &FixStore, report: &AnalysisReport, rule_id: &str, violation: &Violation, incident: &Incident, options: &PromptOptions) -> Result<String, Box<dyn std::error::Error>> {
+    let fixes = store.relevant(rule_id, &incident_code(incident, options.context), options.examples)?;
+    let related = report.related_files(&incident.uri);
+    Ok(build_prompt(rule_id, violation, incident, &fixes, &related, options))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixes::FixExample;
+    use crate::yaml_parser::parse_yaml;
+
+    #[test]
+    fn prompt_includes_the_closest_fixes() {
+        let report = parse_yaml("samples/coolstore_analysis_output.yaml").unwrap();
+        let rule_id = "javax-to-jakarta-import-00001";
+        let violation = report.rulesets.iter().find_map(|r| r.violations.get(rule_id)).unwrap();
+        let incident = violation.incidents.iter().find(|i| i.uri.ends_with("InventoryEntity.java")).unwrap();
+
+        let mut store = FixStore::open_in_memory().unwrap();
+        for (before, after) in [("import javax.inject.Inject;\n", "import jakarta.inject.Inject;\n"),
+            ("import javax.persistence.Entity;\nimport javax.persistence.Table;\n", "import jakarta.persistence.Entity;\nimport jakarta.persistence.Table;\n")] {
+            store.add(&FixExample {
+                rule_id: rule_id.to_string(),
+                file: "src/main/java/Order.java".to_string(),
+                before: before.to_string(),
+                after: after.to_string(),
+                explanation: "Use the jakarta namespace.".to_string(),
+                ..Default::default()
+            }).unwrap();
+        }
+
+        let options = PromptOptions { examples: 1, ..Default::default() };
+        let prompt = incident_prompt(&store, &report, rule_id, violation, incident, &options).unwrap();
+        assert!(prompt.starts_with("# Migration issue\n\nRule: javax-to-jakarta-import-00001\nCategory: mandatory\n"));
+        assert!(prompt.contains("## Example 1\n\nUse the jakarta namespace.\n\nBefore:\n```java\nimport javax.persistence.Entity;\n"));
+        assert!(!prompt.contains("## Example 2") && !prompt.contains("javax.inject"));
+        assert!(prompt.contains("# Related files\n\n- file:///opt/input/source/src/main/java/com/redhat/coolstore/model/CatalogItemEntity.java (package com.redhat.coolstore.model)\n"));
+        assert!(prompt.contains("# Code to fix\n\n```java\npackage com.redhat.coolstore.model;\n"));
+
+        // Examples stored without a trailing newline still get their closing fence on a line of its own.
+        let fix = FixExample { rule_id: rule_id.to_string(), file: "Order.java".to_string(), before: "import x;".to_string(), ..Default::default() };
+        let ranked = RankedFix { score: 1.0, fix };
+        let prompt = build_prompt(rule_id, violation, incident, &[ranked], &[], &options);
+        assert!(prompt.contains("Before:\n```java\nimport x;\n```\n\nAfter:\n```java\n```\n"));
+    }
+}
@@ -0,0 +1,206 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::sync::Arc;
+use serde::{Deserialize, Serialize};
+use crate::grouping::package_of;
+use crate::yaml_parser::{AnalysisReport, Incident};
+
+/// Why two impacted files are related.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Deserialize, Serialize)]
+#[serde(tag = "kind", content = "value", rename_all = "snake_case")]
+pub enum Relation {
+    /// Both files are hit by rules carrying this label.
+    Label(Arc<str>),
+    /// Both files use this Maven `groupId:artifactId`, or one declares the
+    /// dependency and the other references a class of its group.
+    Coordinates(String),
+    /// Both files are in this Java package, or one references a class of
+    /// the package the other is in.
+    Package(String),
+}
+
+impl fmt::Display for Relation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Relation::Label(label) => write!(f, "label {}", label),
+            Relation::Coordinates(coordinates) => write!(f, "maven {}", coordinates),
+            Relation::Package(package) => write!(f, "package {}", package),
+        }
+    }
+}
+
+/// An impacted file related to another one.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct RelatedFile {
+    pub uri: Arc<str>,
+    pub relations: Vec<Relation>,
+}
+
+const SELECTOR_PREFIX: &str = "konveyor.io/";
+const DISCOVERY_LABEL: &str = "discovery";
+
+// What the incidents of one file tell about it.
+#[derive(Debug, Default)]
+struct FileKeys {
+    labels: BTreeSet<Arc<str>>,
+    // `groupId:artifactId`, or just `groupId` when the artifact is unknown.
+    coordinates: BTreeSet<String>,
+    packages: BTreeSet<String>,
+    // Fully qualified names used by the file, e.g. imports.
+    references: BTreeSet<String>,
+}
+
+/// Index of the impacted files of a report, to find the files related to one of them.
+#[derive(Debug, Default)]
+pub struct RelatedFiles {
+    files: BTreeMap<Arc<str>, FileKeys>,
+}
+
+impl RelatedFiles {
+
+    /// Only labels that describe a rule are compared. The `konveyor.io/`
+    /// source, target and include labels select rules for the whole
+    /// analysis, and discovery rules tag nearly every file, so both are ignored.
+    pub fn new(report: &AnalysisReport) -> RelatedFiles {
+        let mut files: BTreeMap<Arc<str>, FileKeys> = BTreeMap::new();
+        for ruleset in &report.rulesets {
+            let rules = ruleset.violations.values().map(|v| (&v.labels, &v.incidents))
+                .chain(ruleset.insights.values().map(|i| (&i.labels, &i.incidents)));
+            for (labels, incidents) in rules {
+                let discovery = labels.iter().any(|label| &**label == DISCOVERY_LABEL);
+                for incident in incidents.iter().filter(|incident| !incident.uri.is_empty()) {
+                    let keys = files.entry(incident.uri.clone()).or_default();
+                    if !discovery {
+                        keys.labels.extend(labels.iter().filter(|label| !label.starts_with(SELECTOR_PREFIX)).cloned());
+                    }
+                    add_incident_keys(keys, incident);
+                }
+            }
+        }
+
+        RelatedFiles { files }
+    }
+
+    /// Files related to `uri`, the most related first. Shared coordinates
+    /// and packages weigh more than shared labels.
+    pub fn related(&self, uri: &str) -> Vec<RelatedFile> {
+        let Some(keys) = self.files.get(uri) else { return Vec::new() };
+        let mut related: Vec<RelatedFile> = self.files.iter()
+            .filter(|(other, _)| &***other != uri)
+            .filter_map(|(other, other_keys)| {
+                let relations = relations(keys, other_keys);
+                (!relations.is_empty()).then(|| RelatedFile { uri: other.clone(), relations })
+            })
+            .collect();
+        let weight = |file: &RelatedFile| {
+            let labels = file.relations.iter().filter(|r| matches!(r, Relation::Label(_))).count();
+            (file.relations.len() - labels, labels)
+        };
+        related.sort_by(|a, b| weight(b).cmp(&weight(a)).then_with(|| a.uri.cmp(&b.uri)));
+        related
+    }
+}
+
+impl AnalysisReport {
+    /// Impacted files related to `uri`, see `RelatedFiles`.
+    pub fn related_files(&self, uri: &str) -> Vec<RelatedFile> {
+        RelatedFiles::new(self).related(uri)
+    }
+}
+
+fn add_incident_keys(keys: &mut FileKeys, incident: &Incident) {
+    let variable = |name: &str| incident.variables.get(name).and_then(|value| value.as_str());
+    if let Some(xml) = variable("matchingXML") {
+        if let Some(group) = xml_text(xml, "groupId") {
+            keys.coordinates.insert(match xml_text(xml, "artifactId") {
+                Some(artifact) => format!("{}:{}", group, artifact),
+                None => group.to_string(),
+            });
+        }
+    }
+    if incident.uri.ends_with(".java") {
+        match variable("package") {
+            Some(package) => { keys.packages.insert(package.to_string()); }
+            None => {
+                let package = package_of(&incident.uri);
+                if !package.starts_with('(') {
+                    keys.packages.insert(package);
+                }
+            }
+        }
+        if let Some(name) = variable("name").filter(|name| name.contains('.')) {
+            keys.references.insert(name.to_string());
+        }
+    }
+}
+
+// Trimmed text of the first `<tag>` element of `xml`.
+fn xml_text<'a>(xml: &'a str, tag: &str) -> Option<&'a str> {
+    let start = xml.find(&format!("<{}>", tag))? + tag.len() + 2;
+    let end = start + xml[start..].find(&format!("</{}>", tag))?;
+    Some(xml[start..end].trim()).filter(|text| !text.is_empty())
+}
+
+// Whether `name` is in `package` or one of its subpackages.
+fn in_package(name: &str, package: &str) -> bool {
+    name.strip_prefix(package).is_some_and(|rest| rest.starts_with('.'))
+}
+
+fn relations(a: &FileKeys, b: &FileKeys) -> Vec<Relation> {
+    let mut relations = BTreeSet::new();
+    relations.extend(a.labels.intersection(&b.labels).cloned().map(Relation::Label));
+    relations.extend(a.coordinates.intersection(&b.coordinates).cloned().map(Relation::Coordinates));
+    relations.extend(a.packages.intersection(&b.packages).cloned().map(Relation::Package));
+    for (x, y) in [(a, b), (b, a)] {
+        for coordinates in &x.coordinates {
+            let group = coordinates.split(':').next().unwrap_or(coordinates);
+            if y.references.iter().any(|name| in_package(name, group)) {
+                relations.insert(Relation::Coordinates(coordinates.clone()));
+            }
+        }
+        for package in &x.packages {
+            // Exact package only: a class of a subpackage is not a dependency.
+            if y.references.iter().any(|name| name.rsplit_once('.').is_some_and(|(p, _)| p == package)) {
+                relations.insert(Relation::Package(package.clone()));
+            }
+        }
+    }
+    relations.into_iter().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::yaml_parser::parse_yaml;
+
+    #[test]
+    fn pom_dependencies_relate_to_java_imports() {
+        let report = parse_yaml("samples/demo-output.yaml").unwrap();
+        let pom = "file:///examples/java/pom.xml";
+        let app = "file:///examples/java/example/src/main/java/com/example/apps/App.java";
+        let fabric8 = vec![
+            Relation::Coordinates("io.fabric8:kubernetes-client".to_string()),
+            Relation::Coordinates("io.fabric8:kubernetes-client-api".to_string()),
+        ];
+
+        let related = report.related_files(app);
+        assert_eq!(related[0], RelatedFile { uri: Arc::from(pom), relations: fabric8.clone() });
+        assert_eq!(related[1].relations, vec![Relation::Package("com.example.apps".to_string())]);
+        assert!(report.related_files(pom).iter().any(|r| &*r.uri == app && r.relations == fabric8));
+        assert!(report.related_files("file:///nowhere").is_empty());
+    }
+
+    #[test]
+    fn java_files_of_one_package() {
+        let report = parse_yaml("samples/coolstore_analysis_output.yaml").unwrap();
+        let model = "file:///opt/input/source/src/main/java/com/redhat/coolstore/model/";
+        let related = report.related_files(&format!("{}Order.java", model));
+        assert_eq!(related[0], RelatedFile {
+            uri: Arc::from(format!("{}OrderItem.java", model)),
+            relations: vec![Relation::Label(Arc::from("hibernate")), Relation::Package("com.redhat.coolstore.model".to_string())],
+        });
+        assert!(related.iter().all(|r| !r.uri.ends_with("/Order.java")));
+        // Discovery tags such as `tag=Java Source` do not relate files on their own.
+        assert_eq!(related.len(), 12);
+    }
+}
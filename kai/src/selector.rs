@@ -0,0 +1,195 @@
+use std::cmp::Ordering;
+use std::sync::Arc;
+use serde::Serialize;
+use crate::yaml_parser::AnalysisReport;
+
+const SOURCE_KEY: &str = "konveyor.io/source";
+const TARGET_KEY: &str = "konveyor.io/target";
+const DISCOVERY_LABEL: &str = "discovery";
+
+// A technology with an optional version, e.g. `eap8`, `jakarta-ee9+` or `java-ee`.
+#[derive(Debug, PartialEq)]
+struct Technology<'a> {
+    name: &'a str,
+    version: Vec<u32>,
+    // `Greater` for `eap8+`, `Less` for `eap8-`, `Equal` for an exact version.
+    range: Ordering,
+}
+
+impl<'a> Technology<'a> {
+    fn parse(value: &'a str) -> Technology<'a> {
+        let (value, range) = match value.strip_suffix('+') {
+            Some(value) => (value, Ordering::Greater),
+            None => match value.strip_suffix('-') {
+                Some(value) => (value, Ordering::Less),
+                None => (value, Ordering::Equal),
+            },
+        };
+        // The version starts at the first digit following a letter, so `jakarta-ee9` is `jakarta-ee` 9.
+        let start = value.char_indices()
+            .find(|&(i, c)| i > 0 && c.is_ascii_digit())
+            .map_or(value.len(), |(i, _)| i);
+        let version: Option<Vec<u32>> = value[start..].split('.').map(|part| part.parse().ok()).collect();
+        match version {
+            Some(version) if start < value.len() => Technology { name: &value[..start], version, range },
+            _ => Technology { name: value, version: Vec::new(), range: Ordering::Equal },
+        }
+    }
+
+    // Whether a rule labelled with `self` applies to the `chosen` technology.
+    // Either side without a version matches every version of the other.
+    fn accepts(&self, chosen: &Technology) -> bool {
+        if self.name != chosen.name {
+            return false;
+        }
+        if self.version.is_empty() || chosen.version.is_empty() {
+            return true;
+        }
+        let ordering = chosen.version.cmp(&self.version);
+        ordering == Ordering::Equal || ordering == self.range
+    }
+}
+
+/// A migration path, as the `--source` and `--target` choices of the analyzer.
+///
+/// A rule is on the path when, for sources and for targets alike, nothing
+/// was chosen, the rule has no label of that kind, or one of its labels
+/// accepts one of the choices. `konveyor.io/target=eap8+` accepts `eap8`,
+/// `eap9` and `eap`, but not `eap7`. Like in the analyzer, a bare
+/// `konveyor.io/source` label accepts any source and discovery rules are
+/// always on the path.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct MigrationPath {
+    pub sources: Vec<String>,
+    pub targets: Vec<String>,
+}
+
+impl MigrationPath {
+    pub fn new<S: Into<String>>(sources: impl IntoIterator<Item = S>, targets: impl IntoIterator<Item = S>) -> MigrationPath {
+        MigrationPath {
+            sources: sources.into_iter().map(Into::into).collect(),
+            targets: targets.into_iter().map(Into::into).collect(),
+        }
+    }
+
+    /// True when neither sources nor targets are chosen, so every rule is on the path.
+    pub fn is_empty(&self) -> bool {
+        self.sources.is_empty() && self.targets.is_empty()
+    }
+
+    /// Whether a rule carrying `labels` is on the path.
+    pub fn selects(&self, labels: &[Arc<str>]) -> bool {
+        if labels.iter().any(|label| &**label == DISCOVERY_LABEL) {
+            return true;
+        }
+        [(SOURCE_KEY, &self.sources), (TARGET_KEY, &self.targets)].into_iter().all(|(key, choices)| {
+            if choices.is_empty() {
+                return true;
+            }
+            let mut values = labels.iter()
+                .filter_map(|label| label.strip_prefix(key))
+                .filter(|rest| rest.is_empty() || rest.starts_with('='))
+                .map(|rest| rest.strip_prefix('=').unwrap_or(rest))
+                .peekable();
+            if values.peek().is_none() {
+                return true;
+            }
+            values.any(|value| {
+                value.is_empty() || choices.iter().any(|choice| Technology::parse(value).accepts(&Technology::parse(choice)))
+            })
+        })
+    }
+}
+
+/// What a ruleset kept on a migration path.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct RulesetContribution {
+    pub ruleset: Arc<str>,
+    pub violations: usize,
+    pub incidents: usize,
+    pub effort: i64,
+}
+
+/// A report narrowed to a migration path.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct ScopedReport {
+    #[serde(skip)]
+    pub report: AnalysisReport,
+    /// Rulesets with violations on the path, most incidents first.
+    pub contributed: Vec<RulesetContribution>,
+    /// Rulesets whose violations are all off the path.
+    pub excluded: Vec<Arc<str>>,
+}
+
+impl AnalysisReport {
+    /// Returns a copy of the report that only keeps the violations and
+    /// insights on `path`, with the rulesets they come from.
+    ///
+    /// Rulesets left without violations or insights are dropped.
+    pub fn scope(&self, path: &MigrationPath) -> ScopedReport {
+        let mut scoped = ScopedReport::default();
+        for ruleset in &self.rulesets {
+            let mut kept = ruleset.clone();
+            kept.violations.retain(|_, violation| path.selects(&violation.labels));
+            kept.insights.retain(|_, insight| path.selects(&insight.labels));
+            if !kept.violations.is_empty() {
+                scoped.contributed.push(RulesetContribution {
+                    ruleset: kept.name.clone(),
+                    violations: kept.violations.len(),
+                    incidents: kept.violations.values().map(|v| v.incidents.len()).sum(),
+                    effort: kept.violations.values().map(|v| v.effort.unwrap_or(0) as i64 * v.incidents.len() as i64).sum(),
+                });
+            } else if !ruleset.violations.is_empty() {
+                scoped.excluded.push(kept.name.clone());
+            }
+            if !kept.violations.is_empty() || !kept.insights.is_empty() {
+                scoped.report.rulesets.push(kept);
+            }
+        }
+        scoped.contributed.sort_by(|a, b| b.incidents.cmp(&a.incidents).then_with(|| a.ruleset.cmp(&b.ruleset)));
+        scoped
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::yaml_parser::parse_yaml;
+
+    fn labels(labels: &[&str]) -> Vec<Arc<str>> {
+        labels.iter().map(|label| Arc::from(*label)).collect()
+    }
+
+    #[test]
+    fn versions_and_wildcards() {
+        let eap8_up = labels(&["konveyor.io/source", "konveyor.io/target=eap8+"]);
+        for (target, selected) in [("eap8", true), ("eap9", true), ("eap", true), ("eap7", false), ("quarkus", false)] {
+            assert_eq!(MigrationPath::new([], [target]).selects(&eap8_up), selected, "{}", target);
+        }
+        let java_ee = labels(&["konveyor.io/source=java-ee", "konveyor.io/target=quarkus"]);
+        assert!(MigrationPath::new(["java-ee"], ["quarkus3"]).selects(&java_ee));
+        assert!(!MigrationPath::new(["springboot"], ["quarkus"]).selects(&java_ee));
+        assert!(MigrationPath::new(["springboot"], []).selects(&eap8_up));
+        assert!(MigrationPath::new(["rmi"], ["quarkus"]).selects(&labels(&["discovery", "konveyor.io/target=discovery"])));
+        assert_eq!(Technology::parse("jakarta-ee9+"), Technology { name: "jakarta-ee", version: vec![9], range: Ordering::Greater });
+        assert_eq!(Technology::parse("openjdk1.8").version, vec![1, 8]);
+    }
+
+    #[test]
+    fn quarkus_path_of_coolstore() {
+        let report = parse_yaml("samples/coolstore_analysis_output.yaml").unwrap();
+        let scoped = report.scope(&MigrationPath::new(["java-ee"], ["quarkus"]));
+        let contributed: Vec<&str> = scoped.contributed.iter().map(|c| &*c.ruleset).collect();
+        // hibernate-00005 of eap8/eap7 also targets `quarkus3+`.
+        assert_eq!(contributed, vec!["quarkus/springboot", "eap8/eap7"]);
+        assert_eq!((scoped.contributed[0].violations, scoped.contributed[1].violations), (20, 1));
+        assert_eq!(scoped.excluded, labels(&["cloud-readiness", "eap7/weblogic/tests/data"]));
+        assert!(scoped.report.rulesets.iter().all(|r| &*r.name != "cloud-readiness" && !r.name.starts_with("azure")));
+        assert!(scoped.report.rulesets.iter().any(|r| &*r.name == "discovery-rules"));
+
+        let everything = report.scope(&MigrationPath::default());
+        assert_eq!((everything.contributed.len(), everything.excluded.len()), (4, 0));
+        let eap = report.scope(&MigrationPath::new([], ["eap8"]));
+        assert_eq!(eap.contributed.iter().map(|c| c.violations).sum::<usize>(), 13);
+    }
+}
@@ -0,0 +1,228 @@
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use axum::extract::{FromRef, Path, Query, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::{Json, Router};
+use serde::{Deserialize, Serialize};
+use crate::export::{incident_records, IncidentRecord};
+use crate::related::{RelatedFile, RelatedFiles};
+use crate::yaml_parser::{AnalysisReport, ImpactedFiles, ReportSummary, Ruleset};
+
+/// Reports served by the HTTP API, keyed by the name used in the URL.
+pub type Reports = Arc<BTreeMap<String, AnalysisReport>>;
+
+// Root that incident paths and fingerprints are taken relative to, see `incident_records`.
+#[derive(Clone)]
+struct SourceRoot(Option<Arc<str>>);
+
+// File indexes of a report, built once when the router is created.
+struct FileIndex {
+    impacted_files: ImpactedFiles,
+    related: RelatedFiles,
+}
+
+type FileIndexes = Arc<BTreeMap<String, FileIndex>>;
+
+#[derive(Clone)]
+struct ServerState {
+    reports: Reports,
+    files: FileIndexes,
+    source_root: SourceRoot,
+}
+
+impl FromRef<ServerState> for Reports {
+    fn from_ref(state: &ServerState) -> Reports {
+        state.reports.clone()
+    }
+}
+
+impl FromRef<ServerState> for FileIndexes {
+    fn from_ref(state: &ServerState) -> FileIndexes {
+        state.files.clone()
+    }
+}
+
+impl FromRef<ServerState> for SourceRoot {
+    fn from_ref(state: &ServerState) -> SourceRoot {
+        state.source_root.clone()
+    }
+}
+
+/// One rule as listed by `GET /reports/{name}/rules`.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct RuleEntry {
+    pub ruleset: Arc<str>,
+    pub rule_id: Arc<str>,
+    pub description: String,
+    pub category: Option<String>,
+    pub effort: Option<i32>,
+    pub labels: Vec<Arc<str>>,
+    pub incidents: usize,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UriQuery {
+    pub uri: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LabelQuery {
+    pub label: String,
+}
+
+/// Builds the router exposing the JSON endpoints:
+///
+/// * `GET /reports` - names of the loaded reports
+/// * `GET /reports/{name}/summary` - totals of one report
+/// * `GET /reports/{name}/files` - impacted file names
+/// * `GET /reports/{name}/files/violations?uri=...` - rulesets impacting one URI, with incident fingerprints
+/// * `GET /reports/{name}/files/related?uri=...` - impacted files related to one URI
+/// * `GET /reports/{name}/rules` - rules with their incident counts
+/// * `GET /reports/{name}/incidents` - every incident with its fingerprint, as exported
+/// * `GET /reports/{name}/violations?label=...` - the report narrowed to one label, with incident fingerprints
+///
+/// Reports are served as given, load them with `Config::load_report` for the
+/// configured selection and suppressions to apply.
+/// Incident paths and fingerprints are taken relative to `source_root`, as `kai export` does.
+/// The per-file endpoints are answered from indexes built here, once per report.
+pub fn router(reports: Reports, source_root: Option<&str>) -> Router {
+    let files = reports.iter()
+        .map(|(name, report)| (name.clone(), FileIndex { impacted_files: report.impacted_files(), related: RelatedFiles::new(report) }))
+        .collect();
+    Router::new()
+        .route("/reports", get(list_reports))
+        .route("/reports/{name}/summary", get(summary))
+        .route("/reports/{name}/files", get(impacted_files))
+        .route("/reports/{name}/files/violations", get(file_violations))
+        .route("/reports/{name}/files/related", get(related_files))
+        .route("/reports/{name}/rules", get(rules))
+        .route("/reports/{name}/incidents", get(incidents))
+        .route("/reports/{name}/violations", get(violations_by_label))
+        .with_state(ServerState { reports, files: Arc::new(files), source_root: SourceRoot(source_root.map(Arc::from)) })
+}
+
+/// Serves `reports` on `addr` until the process is stopped, see `router`.
+pub async fn serve(addr: &str, reports: Reports, source_root: Option<&str>) -> Result<(), Box<dyn std::error::Error>> {
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+    println!("Serving {} report(s) on http://{}", reports.len(), listener.local_addr()?);
+    axum::serve(listener, router(reports, source_root)).await?;
+    Ok(())
+}
+
+/// Rendered as a `404 Not Found` with a JSON `{"error": ...}` body.
+#[derive(Debug)]
+pub struct NotFound(String);
+
+impl IntoResponse for NotFound {
+    fn into_response(self) -> Response {
+        (StatusCode::NOT_FOUND, Json(serde_json::json!({ "error": self.0 }))).into_response()
+    }
+}
+
+fn lookup<'a>(reports: &'a Reports, name: &str) -> Result<&'a AnalysisReport, NotFound> {
+    reports.get(name).ok_or_else(|| NotFound(format!("unknown report `{}`", name)))
+}
+
+fn lookup_files<'a>(files: &'a FileIndexes, name: &str) -> Result<&'a FileIndex, NotFound> {
+    files.get(name).ok_or_else(|| NotFound(format!("unknown report `{}`", name)))
+}
+
+async fn list_reports(State(reports): State<Reports>) -> Json<Vec<String>> {
+    Json(reports.keys().cloned().collect())
+}
+
+async fn summary(State(reports): State<Reports>, Path(name): Path<String>) -> Result<Json<ReportSummary>, NotFound> {
+    Ok(Json(lookup(&reports, &name)?.summary()))
+}
+
+async fn impacted_files(State(reports): State<Reports>, Path(name): Path<String>) -> Result<Json<Vec<Arc<str>>>, NotFound> {
+    Ok(Json(lookup(&reports, &name)?.impacted_file_names()))
+}
+
+// `ruleset` as JSON, with the fingerprint of every incident of its
+// violations added next to the other incident fields.
+fn with_fingerprints(ruleset: &Ruleset, source_root: Option<&str>) -> serde_json::Value {
+    let mut value = serde_json::to_value(ruleset).unwrap_or_default();
+    for (rule_id, violation) in &ruleset.violations {
+        let incidents = value.get_mut("violations")
+            .and_then(|violations| violations.get_mut(&**rule_id))
+            .and_then(|violation| violation.get_mut("incidents"))
+            .and_then(serde_json::Value::as_array_mut);
+        for (incident, fingerprint) in incidents.into_iter().flatten().zip(violation.fingerprints(rule_id, source_root)) {
+            if let Some(incident) = incident.as_object_mut() {
+                incident.insert("fingerprint".to_string(), fingerprint.into());
+            }
+        }
+    }
+    value
+}
+
+async fn file_violations(
+    State(files): State<FileIndexes>,
+    State(SourceRoot(source_root)): State<SourceRoot>,
+    Path(name): Path<String>,
+    Query(query): Query<UriQuery>,
+) -> Result<Json<BTreeMap<Arc<str>, serde_json::Value>>, NotFound> {
+    let rulesets = lookup_files(&files, &name)?.impacted_files
+        .get(query.uri.as_str())
+        .ok_or_else(|| NotFound(format!("`{}` has no violations in report `{}`", query.uri, name)))?;
+    Ok(Json(rulesets.iter()
+        .map(|(name, ruleset)| (name.clone(), with_fingerprints(ruleset, source_root.as_deref())))
+        .collect()))
+}
+
+async fn related_files(
+    State(files): State<FileIndexes>,
+    Path(name): Path<String>,
+    Query(query): Query<UriQuery>,
+) -> Result<Json<Vec<RelatedFile>>, NotFound> {
+    let index = lookup_files(&files, &name)?;
+    if !index.impacted_files.contains_key(query.uri.as_str()) {
+        return Err(NotFound(format!("`{}` has no violations in report `{}`", query.uri, name)));
+    }
+    Ok(Json(index.related.related(&query.uri)))
+}
+
+async fn rules(State(reports): State<Reports>, Path(name): Path<String>) -> Result<Json<Vec<RuleEntry>>, NotFound> {
+    let report = lookup(&reports, &name)?;
+    let mut rules: Vec<RuleEntry> = report.rulesets
+        .iter()
+        .flat_map(|ruleset| {
+            ruleset.violations.iter().map(|(rule_id, violation)| RuleEntry {
+                ruleset: ruleset.name.clone(),
+                rule_id: rule_id.clone(),
+                description: violation.description.clone(),
+                category: violation.category.clone(),
+                effort: violation.effort,
+                labels: violation.labels.clone(),
+                incidents: violation.incidents.len(),
+            })
+        })
+        .collect();
+    rules.sort_by(|a, b| (&a.ruleset, &a.rule_id).cmp(&(&b.ruleset, &b.rule_id)));
+    Ok(Json(rules))
+}
+
+async fn incidents(
+    State(reports): State<Reports>,
+    State(SourceRoot(source_root)): State<SourceRoot>,
+    Path(name): Path<String>,
+) -> Result<Json<Vec<IncidentRecord>>, NotFound> {
+    Ok(Json(incident_records(&name, lookup(&reports, &name)?, source_root.as_deref())))
+}
+
+async fn violations_by_label(
+    State(reports): State<Reports>,
+    State(SourceRoot(source_root)): State<SourceRoot>,
+    Path(name): Path<String>,
+    Query(query): Query<LabelQuery>,
+) -> Result<Json<serde_json::Value>, NotFound> {
+    let filtered = lookup(&reports, &name)?.filter_by_label(&query.label);
+    let rulesets: Vec<serde_json::Value> = filtered.rulesets
+        .iter()
+        .map(|ruleset| with_fingerprints(ruleset, source_root.as_deref()))
+        .collect();
+    Ok(Json(serde_json::json!({ "rulesets": rulesets })))
+}
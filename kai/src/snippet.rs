@@ -0,0 +1,125 @@
+use std::fmt::Write;
+use crate::yaml_parser::Incident;
+
+/// One source line of a code snippet.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SnippetLine {
+    pub number: i32,
+    /// Source text with its original indentation.
+    pub text: String,
+    /// Whether this is the line the incident points at.
+    pub flagged: bool,
+}
+
+/// A `codeSnip` split into numbered lines.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Snippet {
+    pub lines: Vec<SnippetLine>,
+}
+
+impl Snippet {
+    /// Parses a snippet as written by the analyzer, one `<number>  <text>`
+    /// line per source line, flagging `flagged_line`.
+    ///
+    /// Lines without a number prefix are kept as a continuation of the previous line.
+    pub fn parse(code_snip: &str, flagged_line: Option<i32>) -> Snippet {
+        let mut lines: Vec<SnippetLine> = Vec::new();
+        for raw in code_snip.lines() {
+            let trimmed = raw.trim_start();
+            let digits = trimmed.len() - trimmed.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+            match trimmed[..digits].parse::<i32>() {
+                Ok(number) => {
+                    // The number is followed by two spaces, the rest is source indentation.
+                    let rest = &trimmed[digits..];
+                    let text = rest.strip_prefix("  ").or_else(|| rest.strip_prefix(' ')).unwrap_or(rest);
+                    lines.push(SnippetLine { number, text: text.to_string(), flagged: Some(number) == flagged_line });
+                }
+                Err(_) => match lines.last_mut() {
+                    Some(last) => {
+                        last.text.push('\n');
+                        last.text.push_str(raw);
+                    }
+                    None if !raw.trim().is_empty() => lines.push(SnippetLine { number: 0, text: raw.to_string(), flagged: false }),
+                    None => {}
+                },
+            }
+        }
+        Snippet { lines }
+    }
+
+    /// The flagged line, if the snippet contains it.
+    pub fn flagged(&self) -> Option<&SnippetLine> {
+        self.lines.iter().find(|line| line.flagged)
+    }
+
+    /// Keeps `context` lines on each side of the flagged line.
+    ///
+    /// Snippets without a flagged line keep their first `2 * context + 1` lines.
+    pub fn trim(&self, context: usize) -> Snippet {
+        let (start, end) = match self.lines.iter().position(|line| line.flagged) {
+            Some(index) => (index.saturating_sub(context), index.saturating_add(context).saturating_add(1)),
+            None => (0, context.saturating_mul(2).saturating_add(1)),
+        };
+        Snippet { lines: self.lines[start.min(self.lines.len())..end.min(self.lines.len())].to_vec() }
+    }
+
+    /// Renders the snippet with aligned line numbers and a `>` before the
+    /// flagged line. Tabs are expanded to four spaces.
+    pub fn render(&self) -> String {
+        let width = self.lines.iter().map(|line| line.number.to_string().len()).max().unwrap_or(0);
+        let mut out = String::new();
+        for line in &self.lines {
+            let marker = if line.flagged { '>' } else { ' ' };
+            let _ = writeln!(out, "{} {:>width$} | {}", marker, line.number, line.text.replace('\t', "    "), width = width);
+        }
+        out
+    }
+}
+
+impl Incident {
+    /// The code snippet of the incident, with its line flagged.
+    pub fn snippet(&self) -> Option<Snippet> {
+        self.code_snip.as_deref().map(|code_snip| Snippet::parse(code_snip, self.line_number))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::yaml_parser::parse_yaml;
+
+    #[test]
+    fn parse_trim_and_render() {
+        let snippet = Snippet::parse("107  \t\t</dependency>\n108  \t\t\t<artifactId>x</artifactId>\n109    <b>\n110  </b>\n", Some(108));
+        assert_eq!(snippet.lines.len(), 4);
+        assert_eq!(snippet.lines[1], SnippetLine { number: 108, text: "\t\t\t<artifactId>x</artifactId>".to_string(), flagged: true });
+        assert_eq!(snippet.lines[2].text, "  <b>");
+        assert_eq!(snippet.flagged().map(|line| line.number), Some(108));
+
+        let trimmed = snippet.trim(1);
+        assert_eq!(trimmed.lines.iter().map(|line| line.number).collect::<Vec<_>>(), vec![107, 108, 109]);
+        assert_eq!(trimmed.render(), "  107 |         </dependency>\n> 108 |             <artifactId>x</artifactId>\n  109 |   <b>\n");
+        assert_eq!(Snippet::parse("1  a\n2  b\n3  c", None).trim(0).lines.len(), 1);
+        assert_eq!(snippet.trim(usize::MAX).lines.len(), 4);
+        assert_eq!(Snippet::parse("1  a\n2  b", None).trim(usize::MAX).lines.len(), 2);
+    }
+
+    #[test]
+    fn sample_snippets_flag_their_line() {
+        let report = parse_yaml("samples/coolstore_analysis_output.yaml").unwrap();
+        let incidents = report.rulesets.iter()
+            .flat_map(|r| r.violations.values())
+            .flat_map(|v| v.incidents.iter())
+            .filter(|i| i.line_number.is_some());
+        let (mut flagged, mut missing) = (0, 0);
+        for snippet in incidents.filter_map(Incident::snippet) {
+            assert!(snippet.lines.windows(2).all(|w| w[1].number == w[0].number + 1));
+            match snippet.flagged() {
+                Some(_) => flagged += 1,
+                None => missing += 1,
+            }
+        }
+        // A few incidents point outside of the snippet the analyzer attached.
+        assert_eq!((flagged, missing), (1787, 27));
+    }
+}
@@ -0,0 +1,537 @@
+use std::collections::hash_map::Entry;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+use rusqlite::{params, Connection, OptionalExtension};
+use crate::yaml_parser::{AnalysisReport, ImpactedFiles, Incident, Insight, Link, Ruleset, Violation};
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS runs (
+        id          INTEGER PRIMARY KEY AUTOINCREMENT,
+        application TEXT NOT NULL,
+        source      TEXT NOT NULL,
+        imported_at INTEGER NOT NULL,
+        analyzed_at TEXT
+    );
+    CREATE TABLE IF NOT EXISTS rulesets (
+        id          INTEGER PRIMARY KEY AUTOINCREMENT,
+        run_id      INTEGER NOT NULL REFERENCES runs(id) ON DELETE CASCADE,
+        name        TEXT NOT NULL,
+        description TEXT NOT NULL,
+        tags        TEXT NOT NULL,
+        errors      TEXT NOT NULL,
+        unmatched   TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS violations (
+        id          INTEGER PRIMARY KEY AUTOINCREMENT,
+        ruleset_id  INTEGER NOT NULL REFERENCES rulesets(id) ON DELETE CASCADE,
+        rule_id     TEXT NOT NULL,
+        description TEXT NOT NULL,
+        category    TEXT,
+        effort      INTEGER
+    );
+    CREATE TABLE IF NOT EXISTS labels (
+        violation_id INTEGER NOT NULL REFERENCES violations(id) ON DELETE CASCADE,
+        label        TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS links (
+        violation_id INTEGER NOT NULL REFERENCES violations(id) ON DELETE CASCADE,
+        url          TEXT NOT NULL,
+        title        TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS incidents (
+        id           INTEGER PRIMARY KEY AUTOINCREMENT,
+        violation_id INTEGER NOT NULL REFERENCES violations(id) ON DELETE CASCADE,
+        uri          TEXT NOT NULL,
+        message      TEXT NOT NULL,
+        code_snip    TEXT,
+        line_number  INTEGER,
+        variables    TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS insights (
+        id          INTEGER PRIMARY KEY AUTOINCREMENT,
+        ruleset_id  INTEGER NOT NULL REFERENCES rulesets(id) ON DELETE CASCADE,
+        rule_id     TEXT NOT NULL,
+        description TEXT NOT NULL,
+        category    TEXT,
+        labels      TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS insight_incidents (
+        id          INTEGER PRIMARY KEY AUTOINCREMENT,
+        insight_id  INTEGER NOT NULL REFERENCES insights(id) ON DELETE CASCADE,
+        uri         TEXT NOT NULL,
+        message     TEXT NOT NULL,
+        code_snip   TEXT,
+        line_number INTEGER,
+        variables   TEXT NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS idx_rulesets_run ON rulesets(run_id);
+    CREATE INDEX IF NOT EXISTS idx_violations_ruleset ON violations(ruleset_id);
+    CREATE INDEX IF NOT EXISTS idx_labels_violation ON labels(violation_id);
+    CREATE INDEX IF NOT EXISTS idx_labels_label ON labels(label);
+    CREATE INDEX IF NOT EXISTS idx_links_violation ON links(violation_id);
+    CREATE INDEX IF NOT EXISTS idx_incidents_violation ON incidents(violation_id);
+    CREATE INDEX IF NOT EXISTS idx_incidents_uri ON incidents(uri);
+    CREATE INDEX IF NOT EXISTS idx_insights_ruleset ON insights(ruleset_id);
+    CREATE INDEX IF NOT EXISTS idx_insight_incidents_insight ON insight_incidents(insight_id);
+";
+
+/// One imported analysis run of an application.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Run {
+    pub id: i64,
+    pub application: String,
+    pub source: String,
+    pub imported_at: i64,
+    /// When the analysis ran, as given to `import_report_at`.
+    pub analyzed_at: Option<String>,
+}
+
+/// SQLite backed index of parsed analysis reports.
+///
+/// Every call to `import_report` records a new run, so the history of an
+/// application is kept across analyses. Queries take the run id and return
+/// the same types as the in-memory `AnalysisReport` API.
+pub struct ReportStore {
+    conn: Connection,
+}
+
+impl ReportStore {
+
+    pub fn open(path: &str) -> Result<ReportStore, Box<dyn std::error::Error>> {
+        ReportStore::init(Connection::open(path)?)
+    }
+
+    pub fn open_in_memory() -> Result<ReportStore, Box<dyn std::error::Error>> {
+        ReportStore::init(Connection::open_in_memory()?)
+    }
+
+    fn init(conn: Connection) -> Result<ReportStore, Box<dyn std::error::Error>> {
+        conn.execute_batch("PRAGMA foreign_keys = ON;")?;
+        conn.execute_batch(SCHEMA)?;
+        // Stores created before runs had an analysis time.
+        if conn.prepare("SELECT analyzed_at FROM runs").is_err() {
+            conn.execute_batch("ALTER TABLE runs ADD COLUMN analyzed_at TEXT;")?;
+        }
+        Ok(ReportStore { conn })
+    }
+
+    /// Imports `report` as a new run of `application` and returns the run id.
+    ///
+    /// `source` is a free form description of where the report came from,
+    /// typically the path of the YAML file.
+    pub fn import_report(&mut self, application: &str, source: &str, report: &AnalysisReport) -> Result<i64, Box<dyn std::error::Error>> {
+        self.insert_run(application, source, None, report)
+    }
+
+    /// Imports `report` like `import_report`, recording that the analysis
+    /// ran at `analyzed_at`, an ISO 8601 date or date-time.
+    pub fn import_report_at(&mut self, application: &str, source: &str, analyzed_at: &str, report: &AnalysisReport) -> Result<i64, Box<dyn std::error::Error>> {
+        self.insert_run(application, source, Some(analyzed_at), report)
+    }
+
+    fn insert_run(&mut self, application: &str, source: &str, analyzed_at: Option<&str>, report: &AnalysisReport) -> Result<i64, Box<dyn std::error::Error>> {
+        let imported_at = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() as i64;
+        let tx = self.conn.transaction()?;
+        tx.execute(
+            "INSERT INTO runs (application, source, imported_at, analyzed_at) VALUES (?1, ?2, ?3, ?4)",
+            params![application, source, imported_at, analyzed_at],
+        )?;
+        let run_id = tx.last_insert_rowid();
+        {
+            let mut insert_ruleset = tx.prepare(
+                "INSERT INTO rulesets (run_id, name, description, tags, errors, unmatched) VALUES (?1, ?2, ?3, ?4, ?5, ?6)")?;
+            let mut insert_violation = tx.prepare(
+                "INSERT INTO violations (ruleset_id, rule_id, description, category, effort) VALUES (?1, ?2, ?3, ?4, ?5)")?;
+            let mut insert_label = tx.prepare(
+                "INSERT INTO labels (violation_id, label) VALUES (?1, ?2)")?;
+            let mut insert_link = tx.prepare(
+                "INSERT INTO links (violation_id, url, title) VALUES (?1, ?2, ?3)")?;
+            let mut insert_incident = tx.prepare(
+                "INSERT INTO incidents (violation_id, uri, message, code_snip, line_number, variables) VALUES (?1, ?2, ?3, ?4, ?5, ?6)")?;
+            let mut insert_insight = tx.prepare(
+                "INSERT INTO insights (ruleset_id, rule_id, description, category, labels) VALUES (?1, ?2, ?3, ?4, ?5)")?;
+            let mut insert_insight_incident = tx.prepare(
+                "INSERT INTO insight_incidents (insight_id, uri, message, code_snip, line_number, variables) VALUES (?1, ?2, ?3, ?4, ?5, ?6)")?;
+
+            for ruleset in &report.rulesets {
+                insert_ruleset.execute(params![
+                    run_id,
+                    ruleset.name,
+                    ruleset.description,
+                    serde_json::to_string(&ruleset.tags)?,
+                    serde_json::to_string(&ruleset.errors)?,
+                    serde_json::to_string(&ruleset.unmatched)?,
+                ])?;
+                let ruleset_id = tx.last_insert_rowid();
+
+                for (rule_id, violation) in &ruleset.violations {
+                    insert_violation.execute(params![
+                        ruleset_id,
+                        rule_id,
+                        violation.description,
+                        violation.category,
+                        violation.effort,
+                    ])?;
+                    let violation_id = tx.last_insert_rowid();
+
+                    for label in &violation.labels {
+                        insert_label.execute(params![violation_id, label])?;
+                    }
+                    for link in &violation.links {
+                        insert_link.execute(params![violation_id, link.url, link.title])?;
+                    }
+                    for incident in &violation.incidents {
+                        insert_incident.execute(params![
+                            violation_id,
+                            incident.uri,
+                            incident.message,
+                            incident.code_snip,
+                            incident.line_number,
+                            serde_json::to_string(&incident.variables)?,
+                        ])?;
+                    }
+                }
+
+                for (rule_id, insight) in &ruleset.insights {
+                    insert_insight.execute(params![
+                        ruleset_id,
+                        rule_id,
+                        insight.description,
+                        insight.category,
+                        serde_json::to_string(&insight.labels)?,
+                    ])?;
+                    let insight_id = tx.last_insert_rowid();
+
+                    for incident in &insight.incidents {
+                        insert_insight_incident.execute(params![
+                            insight_id,
+                            incident.uri,
+                            incident.message,
+                            incident.code_snip,
+                            incident.line_number,
+                            serde_json::to_string(&incident.variables)?,
+                        ])?;
+                    }
+                }
+            }
+        }
+        tx.commit()?;
+        Ok(run_id)
+    }
+
+    /// Returns every imported run of `application`, oldest first.
+    pub fn runs(&self, application: &str) -> Result<Vec<Run>, Box<dyn std::error::Error>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, application, source, imported_at, analyzed_at FROM runs WHERE application = ?1 ORDER BY id")?;
+        let runs = stmt
+            .query_map(params![application], |row| {
+                Ok(Run {
+                    id: row.get(0)?,
+                    application: row.get(1)?,
+                    source: row.get(2)?,
+                    imported_at: row.get(3)?,
+                    analyzed_at: row.get(4)?,
+                })
+            })?
+            .collect::<Result<Vec<Run>, _>>()?;
+        Ok(runs)
+    }
+
+    /// Returns the id of the most recent run of `application`, if any.
+    pub fn latest_run(&self, application: &str) -> Result<Option<i64>, Box<dyn std::error::Error>> {
+        let run_id = self.conn
+            .query_row(
+                "SELECT MAX(id) FROM runs WHERE application = ?1",
+                params![application],
+                |row| row.get::<_, Option<i64>>(0),
+            )
+            .optional()?
+            .flatten();
+        Ok(run_id)
+    }
+
+    /// Removes a run and everything imported with it.
+    pub fn delete_run(&mut self, run_id: i64) -> Result<(), Box<dyn std::error::Error>> {
+        self.conn.execute("DELETE FROM runs WHERE id = ?1", params![run_id])?;
+        Ok(())
+    }
+
+    /// Rebuilds the full `AnalysisReport` of a run.
+    pub fn load_report(&self, run_id: i64) -> Result<AnalysisReport, Box<dyn std::error::Error>> {
+        self.query_report(run_id, None)
+    }
+
+    pub fn impacted_file_names(&self, run_id: i64) -> Result<Vec<Arc<str>>, Box<dyn std::error::Error>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT DISTINCT i.uri
+               FROM incidents i
+               JOIN violations v ON v.id = i.violation_id
+               JOIN rulesets r ON r.id = v.ruleset_id
+              WHERE r.run_id = ?1
+              ORDER BY i.uri")?;
+        let uris = stmt
+            .query_map(params![run_id], |row| row.get(0))?
+            .collect::<Result<Vec<Arc<str>>, _>>()?;
+        Ok(uris)
+    }
+
+    /// SQL counterpart of `AnalysisReport::impacted_files`.
+    pub fn impacted_files(&self, run_id: i64) -> Result<ImpactedFiles, Box<dyn std::error::Error>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT r.name, r.description, v.id, v.rule_id, v.description, v.category, v.effort,
+                    i.uri, i.message, i.code_snip, i.line_number, i.variables
+               FROM incidents i
+               JOIN violations v ON v.id = i.violation_id
+               JOIN rulesets r ON r.id = v.ruleset_id
+              WHERE r.run_id = ?1
+              ORDER BY i.id")?;
+        let mut rows = stmt.query(params![run_id])?;
+        let mut violation_details = HashMap::<i64, (Vec<Arc<str>>, Vec<Link>)>::new();
+        let mut impacted_files = ImpactedFiles::new();
+
+        while let Some(row) = rows.next()? {
+            let ruleset_name: Arc<str> = row.get(0)?;
+            let ruleset_description: String = row.get(1)?;
+            let violation_id: i64 = row.get(2)?;
+            let rule_id: Arc<str> = row.get(3)?;
+            let description: String = row.get(4)?;
+            let category: Option<String> = row.get(5)?;
+            let effort: Option<i32> = row.get(6)?;
+            let incident = incident_from_row(row, 7)?;
+
+            if let Entry::Vacant(entry) = violation_details.entry(violation_id) {
+                entry.insert((self.labels(violation_id)?, self.links(violation_id)?));
+            }
+            let (labels, links) = &violation_details[&violation_id];
+
+            let ruleset = impacted_files
+                .entry(incident.uri.clone())
+                .or_default()
+                .entry(ruleset_name.clone())
+                .or_insert_with(move || Ruleset {
+                    name: ruleset_name,
+                    description: ruleset_description,
+                    ..Default::default()
+                });
+            ruleset.violations
+                .entry(rule_id)
+                .or_insert_with(move || Violation {
+                    description,
+                    category,
+                    labels: labels.clone(),
+                    incidents: Vec::new(),
+                    links: links.clone(),
+                    effort,
+                })
+                .incidents
+                .push(incident);
+        }
+        Ok(impacted_files)
+    }
+
+    /// SQL counterpart of `AnalysisReport::violation_counts`.
+    pub fn violation_counts(&self, run_id: i64) -> Result<BTreeMap<Arc<str>, usize>, Box<dyn std::error::Error>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT v.rule_id, COUNT(i.id)
+               FROM violations v
+               JOIN rulesets r ON r.id = v.ruleset_id
+               LEFT JOIN incidents i ON i.violation_id = v.id
+              WHERE r.run_id = ?1
+              GROUP BY v.rule_id")?;
+        let counts = stmt
+            .query_map(params![run_id], |row| Ok((row.get(0)?, row.get::<_, i64>(1)? as usize)))?
+            .collect::<Result<BTreeMap<Arc<str>, usize>, _>>()?;
+        Ok(counts)
+    }
+
+    /// SQL counterpart of `AnalysisReport::filter_by_label`.
+    pub fn filter_by_label(&self, run_id: i64, label: &str) -> Result<AnalysisReport, Box<dyn std::error::Error>> {
+        self.query_report(run_id, Some(label))
+    }
+
+    fn query_report(&self, run_id: i64, label: Option<&str>) -> Result<AnalysisReport, Box<dyn std::error::Error>> {
+        let mut ruleset_stmt = self.conn.prepare(
+            "SELECT id, name, description, tags, errors, unmatched FROM rulesets WHERE run_id = ?1 ORDER BY id")?;
+        let mut violation_stmt = self.conn.prepare(
+            "SELECT id, rule_id, description, category, effort
+               FROM violations v
+              WHERE ruleset_id = ?1
+                AND (?2 IS NULL OR EXISTS (SELECT 1 FROM labels l WHERE l.violation_id = v.id AND l.label = ?2))
+              ORDER BY id")?;
+        let mut incident_stmt = self.conn.prepare(
+            "SELECT uri, message, code_snip, line_number, variables FROM incidents WHERE violation_id = ?1 ORDER BY id")?;
+        let mut insight_stmt = self.conn.prepare(
+            "SELECT id, rule_id, description, category, labels FROM insights WHERE ruleset_id = ?1 ORDER BY id")?;
+        let mut insight_incident_stmt = self.conn.prepare(
+            "SELECT uri, message, code_snip, line_number, variables FROM insight_incidents WHERE insight_id = ?1 ORDER BY id")?;
+
+        let mut rulesets = Vec::new();
+        let mut ruleset_rows = ruleset_stmt.query(params![run_id])?;
+        while let Some(row) = ruleset_rows.next()? {
+            let ruleset_id: i64 = row.get(0)?;
+            let mut ruleset = Ruleset {
+                name: row.get(1)?,
+                description: row.get(2)?,
+                tags: serde_json::from_str(&row.get::<_, String>(3)?)?,
+                errors: serde_json::from_str(&row.get::<_, String>(4)?)?,
+                unmatched: serde_json::from_str(&row.get::<_, String>(5)?)?,
+                ..Default::default()
+            };
+
+            let mut violation_rows = violation_stmt.query(params![ruleset_id, label])?;
+            while let Some(row) = violation_rows.next()? {
+                let violation_id: i64 = row.get(0)?;
+                let incidents = incident_stmt
+                    .query_map(params![violation_id], |row| incident_from_row(row, 0))?
+                    .collect::<Result<Vec<Incident>, _>>()?;
+                let violation = Violation {
+                    description: row.get(2)?,
+                    category: row.get(3)?,
+                    labels: self.labels(violation_id)?,
+                    incidents,
+                    links: self.links(violation_id)?,
+                    effort: row.get(4)?,
+                };
+                ruleset.violations.insert(row.get(1)?, violation);
+            }
+
+            if label.is_some() && ruleset.violations.is_empty() {
+                continue;
+            }
+
+            let mut insight_rows = insight_stmt.query(params![ruleset_id])?;
+            while let Some(row) = insight_rows.next()? {
+                let insight_id: i64 = row.get(0)?;
+                let incidents = insight_incident_stmt
+                    .query_map(params![insight_id], |row| incident_from_row(row, 0))?
+                    .collect::<Result<Vec<Incident>, _>>()?;
+                let insight = Insight {
+                    description: row.get(2)?,
+                    category: row.get(3)?,
+                    labels: serde_json::from_str(&row.get::<_, String>(4)?)?,
+                    incidents,
+                };
+                ruleset.insights.insert(row.get(1)?, insight);
+            }
+            rulesets.push(ruleset);
+        }
+        let mut report = AnalysisReport { rulesets };
+        report.intern();
+        Ok(report)
+    }
+
+    fn labels(&self, violation_id: i64) -> Result<Vec<Arc<str>>, rusqlite::Error> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT label FROM labels WHERE violation_id = ?1 ORDER BY rowid")?;
+        let labels = stmt
+            .query_map(params![violation_id], |row| row.get(0))?
+            .collect();
+        labels
+    }
+
+    fn links(&self, violation_id: i64) -> Result<Vec<Link>, rusqlite::Error> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT url, title FROM links WHERE violation_id = ?1 ORDER BY rowid")?;
+        let links = stmt
+            .query_map(params![violation_id], |row| Ok(Link { url: row.get(0)?, title: row.get(1)? }))?
+            .collect();
+        links
+    }
+}
+
+// Reads the incident columns (uri, message, code_snip, line_number, variables)
+// starting at `offset`.
+fn incident_from_row(row: &rusqlite::Row, offset: usize) -> Result<Incident, rusqlite::Error> {
+    let variables: String = row.get(offset + 4)?;
+    Ok(Incident {
+        uri: row.get(offset)?,
+        message: row.get(offset + 1)?,
+        code_snip: row.get(offset + 2)?,
+        line_number: row.get(offset + 3)?,
+        variables: serde_json::from_str(&variables).map_err(|e| {
+            rusqlite::Error::FromSqlConversionFailure(offset + 4, rusqlite::types::Type::Text, Box::new(e))
+        })?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::yaml_parser::parse_yaml;
+
+    #[test]
+    fn import_and_query_matches_in_memory() {
+        let report = parse_yaml("samples/demo-output.yaml").unwrap();
+        let mut store = ReportStore::open_in_memory().unwrap();
+        let run_id = store.import_report("demo", "samples/demo-output.yaml", &report).unwrap();
+
+        assert_eq!(store.impacted_file_names(run_id).unwrap(), report.impacted_file_names());
+        assert_eq!(store.violation_counts(run_id).unwrap(), report.violation_counts());
+
+        let impacted_files = store.impacted_files(run_id).unwrap();
+        let expected = report.impacted_files();
+        assert_eq!(impacted_files.len(), expected.len());
+        let ruleset = &impacted_files["file:///examples/customers-tomcat-legacy/pom.xml"]["konveyor-analysis"];
+        assert_eq!(ruleset.violations.len(), 2);
+        let violation = &ruleset.violations["chain-pom-001"];
+        assert_eq!(violation.incidents.len(), 17);
+        assert_eq!(violation.incidents[0].line_number, Some(45));
+        assert_eq!(violation.category.as_deref(), Some("potential"));
+    }
+
+    #[test]
+    fn label_filter_matches_in_memory() {
+        let report = parse_yaml("samples/coolstore_analysis_output.yaml").unwrap();
+        let mut store = ReportStore::open_in_memory().unwrap();
+        let run_id = store.import_report("coolstore", "samples/coolstore_analysis_output.yaml", &report).unwrap();
+
+        let label = "konveyor.io/target=quarkus";
+        let filtered = store.filter_by_label(run_id, label).unwrap();
+        let expected = report.filter_by_label(label);
+        assert!(!expected.rulesets.is_empty());
+        assert_eq!(filtered.violation_counts(), expected.violation_counts());
+        assert_eq!(filtered.impacted_file_names(), expected.impacted_file_names());
+
+        let loaded = store.load_report(run_id).unwrap();
+        assert_eq!(loaded.rulesets.len(), 26);
+        assert_eq!(loaded.violation_counts(), report.violation_counts());
+    }
+
+    #[test]
+    fn load_report_keeps_insights() {
+        let report = parse_yaml("samples/coolstore_analysis_output.yaml").unwrap();
+        let mut store = ReportStore::open_in_memory().unwrap();
+        let run_id = store.import_report("coolstore", "samples/coolstore_analysis_output.yaml", &report).unwrap();
+
+        let loaded = store.load_report(run_id).unwrap();
+        let insights = |report: &AnalysisReport| report.rulesets
+            .iter()
+            .flat_map(|ruleset| ruleset.insights.iter().map(move |(id, insight)| (ruleset.name.clone(), id.clone(), insight.clone())))
+            .collect::<Vec<_>>();
+        let expected = insights(&report);
+        assert!(!expected.is_empty());
+        assert_eq!(insights(&loaded), expected);
+    }
+
+    #[test]
+    fn history_across_runs() {
+        let report = parse_yaml("samples/demo-output.yaml").unwrap();
+        let mut store = ReportStore::open_in_memory().unwrap();
+        assert_eq!(store.latest_run("demo").unwrap(), None);
+
+        let first = store.import_report("demo", "run-1", &report).unwrap();
+        let second = store.import_report_at("demo", "run-2", "2024-03-01", &report).unwrap();
+        store.import_report("other", "run-1", &report).unwrap();
+
+        let runs = store.runs("demo").unwrap();
+        assert_eq!(runs.iter().map(|r| r.id).collect::<Vec<_>>(), vec![first, second]);
+        assert_eq!(runs[0].analyzed_at, None);
+        assert_eq!(runs[1].analyzed_at.as_deref(), Some("2024-03-01"));
+        assert_eq!(store.latest_run("demo").unwrap(), Some(second));
+
+        store.delete_run(second).unwrap();
+        assert_eq!(store.latest_run("demo").unwrap(), Some(first));
+        assert!(store.impacted_file_names(second).unwrap().is_empty());
+    }
+}
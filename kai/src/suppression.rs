@@ -0,0 +1,287 @@
+use std::fs;
+use std::sync::Arc;
+use glob::{MatchOptions, Pattern};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use crate::yaml_parser::{AnalysisReport, Incident};
+
+/// One entry of a suppression file.
+///
+/// `uri` is a glob matched against the incident URI, either in full
+/// (`file:///app/**/pom.xml`) or against its path (`**/pom.xml`).
+/// `fingerprint` is the fingerprint of a single incident, see
+/// `Violation::fingerprints`, which keeps matching when the code moves; at
+/// least one of the two is required. Fingerprint entries need a configured
+/// `source_root`, see `check_source_root`.
+/// `message` is an optional regex that must match somewhere in the incident message.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct Suppression {
+    pub rule_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub uri: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fingerprint: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub line: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    #[serde(default)]
+    pub justification: String,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(default)]
+struct SuppressionFile {
+    suppressions: Vec<Suppression>,
+}
+
+/// An incident removed from a report together with the suppression that matched it.
+#[derive(Clone, Debug)]
+pub struct SuppressedIncident {
+    pub ruleset: Arc<str>,
+    pub rule_id: Arc<str>,
+    pub incident: Incident,
+    pub suppression: Suppression,
+}
+
+/// Result of `Suppressions::apply`.
+#[derive(Clone, Debug, Default)]
+pub struct SuppressionResult {
+    /// The report without the suppressed incidents.
+    pub report: AnalysisReport,
+    pub suppressed: Vec<SuppressedIncident>,
+    /// Suppressions that did not match any incident.
+    pub unused: Vec<Suppression>,
+}
+
+struct Compiled {
+    suppression: Suppression,
+    uri: Option<Pattern>,
+    message: Option<Regex>,
+}
+
+impl Compiled {
+    // `fingerprint` is only computed when a suppression of the rule needs it.
+    fn matches(&self, rule_id: &str, incident: &Incident, fingerprint: Option<&str>) -> bool {
+        let options = MatchOptions { require_literal_separator: true, ..Default::default() };
+        let path = incident.uri.strip_prefix("file://").unwrap_or(&incident.uri);
+        self.suppression.rule_id == rule_id
+            && self.uri.as_ref().is_none_or(|uri| uri.matches_with(&incident.uri, options) || uri.matches_with(path, options))
+            && self.suppression.line.is_none_or(|line| incident.line_number == Some(line))
+            && self.message.as_ref().is_none_or(|re| re.is_match(&incident.message))
+            && self.suppression.fingerprint.as_deref().is_none_or(|expected| fingerprint == Some(expected))
+    }
+}
+
+/// A validated set of suppressions, ready to be applied to reports.
+pub struct Suppressions {
+    compiled: Vec<Compiled>,
+}
+
+impl Suppressions {
+
+    /// Parses and validates a YAML suppression file.
+    ///
+    /// ```yaml
+    /// suppressions:
+    /// - rule_id: xml-pom-001
+    ///   uri: "**/pom.xml"
+    ///   line: 117
+    ///   message: "logback"
+    ///   justification: Logback stays on the classpath
+    /// - rule_id: javax-to-jakarta-import-00001
+    ///   fingerprint: 3f6c2a9e41d07b85
+    ///   justification: Generated code
+    /// ```
+    pub fn load(path: &str) -> Result<Suppressions, Box<dyn std::error::Error>> {
+        let contents = fs::read_to_string(path)?;
+        Suppressions::parse(&contents)
+    }
+
+    pub fn parse(contents: &str) -> Result<Suppressions, Box<dyn std::error::Error>> {
+        let file: SuppressionFile = serde_yaml::from_str(contents)?;
+        let mut compiled = Vec::new();
+        let mut errors = Vec::new();
+        for (index, suppression) in file.suppressions.into_iter().enumerate() {
+            let entry = index + 1;
+            if suppression.rule_id.trim().is_empty() {
+                errors.push(format!("suppression #{}: missing rule_id", entry));
+            }
+            if suppression.justification.trim().is_empty() {
+                errors.push(format!("suppression #{} ({}): missing justification", entry, suppression.rule_id));
+            }
+            if suppression.uri.is_none() && suppression.fingerprint.is_none() {
+                errors.push(format!("suppression #{} ({}): missing uri or fingerprint", entry, suppression.rule_id));
+            }
+            let uri = suppression.uri
+                .as_deref()
+                .map(Pattern::new)
+                .transpose()
+                .map_err(|e| errors.push(format!("suppression #{} ({}): invalid uri glob: {}", entry, suppression.rule_id, e)));
+            let message = suppression.message
+                .as_deref()
+                .map(Regex::new)
+                .transpose()
+                .map_err(|e| errors.push(format!("suppression #{} ({}): invalid message regex: {}", entry, suppression.rule_id, e)));
+            if let (Ok(uri), Ok(message)) = (uri, message) {
+                compiled.push(Compiled { suppression, uri, message });
+            }
+        }
+        if !errors.is_empty() {
+            return Err(errors.join("\n").into());
+        }
+        Ok(Suppressions { compiled })
+    }
+
+    pub fn len(&self) -> usize {
+        self.compiled.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.compiled.is_empty()
+    }
+
+    /// Fails when entries match on fingerprints and no `source_root` is
+    /// configured: fingerprints would hash the absolute paths of the
+    /// analysis and stop matching as soon as the sources are analyzed from
+    /// another directory.
+    pub fn check_source_root(&self, source_root: Option<&str>) -> Result<(), Box<dyn std::error::Error>> {
+        if source_root.is_some() {
+            return Ok(());
+        }
+        let errors: Vec<String> = self.compiled
+            .iter()
+            .enumerate()
+            .filter(|(_, c)| c.suppression.fingerprint.is_some())
+            .map(|(index, c)| format!("suppression #{} ({}): fingerprint entries need source_root to be configured", index + 1, c.suppression.rule_id))
+            .collect();
+        if !errors.is_empty() {
+            return Err(errors.join("\n").into());
+        }
+        Ok(())
+    }
+
+    /// Removes suppressed incidents from `report`.
+    ///
+    /// Fingerprints are taken relative to `source_root`, as everywhere else,
+    /// see `check_source_root`.
+    /// Violations left without incidents are dropped, rulesets are kept.
+    pub fn apply(&self, report: &AnalysisReport, source_root: Option<&str>) -> SuppressionResult {
+        let mut used = vec![false; self.compiled.len()];
+        let mut suppressed = Vec::new();
+        let mut filtered = report.clone();
+
+        for ruleset in &mut filtered.rulesets {
+            for (rule_id, violation) in ruleset.violations.iter_mut() {
+                let fingerprints = self.compiled
+                    .iter()
+                    .any(|c| c.suppression.fingerprint.is_some() && *c.suppression.rule_id == **rule_id)
+                    .then(|| violation.fingerprints(rule_id, source_root));
+                let mut position = 0;
+                violation.incidents.retain(|incident| {
+                    let fingerprint = fingerprints.as_ref().map(|fingerprints| fingerprints[position].as_str());
+                    position += 1;
+                    let matching: Vec<usize> = (0..self.compiled.len())
+                        .filter(|index| self.compiled[*index].matches(rule_id, incident, fingerprint))
+                        .collect();
+                    // Every matching suppression counts as used, the first one is reported.
+                    matching.iter().for_each(|index| used[*index] = true);
+                    match matching.first() {
+                        Some(index) => {
+                            suppressed.push(SuppressedIncident {
+                                ruleset: ruleset.name.clone(),
+                                rule_id: rule_id.clone(),
+                                incident: incident.clone(),
+                                suppression: self.compiled[*index].suppression.clone(),
+                            });
+                            false
+                        }
+                        None => true,
+                    }
+                });
+            }
+            ruleset.violations.retain(|_, violation| !violation.incidents.is_empty());
+        }
+
+        let unused = self.compiled
+            .iter()
+            .zip(used)
+            .filter(|(_, used)| !used)
+            .map(|(c, _)| c.suppression.clone())
+            .collect();
+        SuppressionResult { report: filtered, suppressed, unused }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::yaml_parser::parse_yaml;
+
+    #[test]
+    fn suppresses_matching_incidents() {
+        let report = parse_yaml("samples/demo-output.yaml").unwrap();
+        let violation = &report.rulesets[0].violations["builtin-inclusion-test-json"];
+        let builtin = &violation.incidents[0];
+        let fingerprint = &violation.fingerprints("builtin-inclusion-test-json", Some("/examples"))[0];
+        let suppressions = Suppressions::parse(&format!(r#"
+suppressions:
+- rule_id: chain-pom-001
+  uri: "**/customers-tomcat-legacy/pom.xml"
+  message: "logback"
+  justification: Logging stays as is
+- rule_id: xml-pom-001
+  uri: "file:///examples/customers-tomcat-legacy/pom.xml"
+  line: 117
+  justification: Reviewed
+- rule_id: xml-pom-001
+  uri: "**/customers-tomcat-legacy/pom.xml"
+  line: 117
+  justification: Also reviewed
+- rule_id: chain-pom-001
+  uri: "**/does-not-exist.xml"
+  justification: Left over
+- rule_id: builtin-inclusion-test-json
+  fingerprint: {}
+  justification: Test fixture
+"#, fingerprint)).unwrap();
+        assert_eq!(suppressions.len(), 5);
+        assert!(suppressions.check_source_root(Some("/examples")).is_ok());
+        let error = suppressions.check_source_root(None).err().unwrap().to_string();
+        assert_eq!(error, "suppression #5 (builtin-inclusion-test-json): fingerprint entries need source_root to be configured");
+
+        let result = suppressions.apply(&report, Some("/examples"));
+        let before = report.violation_counts();
+        let after = result.report.violation_counts();
+        let suppressed_chain = result.suppressed.iter().filter(|s| &*s.rule_id == "chain-pom-001").count();
+        assert!(suppressed_chain > 0);
+        assert!(result.suppressed.iter().filter(|s| &*s.rule_id == "chain-pom-001").all(|s| s.incident.message.contains("logback")));
+        assert_eq!(after["chain-pom-001"], before["chain-pom-001"] - suppressed_chain);
+        assert!(result.suppressed.iter().any(|s| &*s.rule_id == "xml-pom-001" && s.suppression.justification == "Reviewed"));
+        assert!(result.suppressed.iter().all(|s| s.suppression.justification != "Also reviewed"));
+        let fixtures: Vec<&Incident> = result.suppressed.iter().filter(|s| s.suppression.justification == "Test fixture").map(|s| &s.incident).collect();
+        assert_eq!(fixtures, vec![builtin]);
+
+        assert_eq!(result.unused.len(), 1);
+        assert_eq!(result.unused[0].justification, "Left over");
+    }
+
+    #[test]
+    fn rejects_invalid_entries() {
+        let error = Suppressions::parse(r#"
+suppressions:
+- rule_id: chain-pom-001
+  uri: "**/pom.xml"
+- rule_id: xml-pom-001
+  uri: "[pom.xml"
+  message: "("
+  justification: Broken patterns
+- rule_id: xml-pom-001
+  justification: Matches nothing
+"#).err().unwrap().to_string();
+        assert!(error.contains("#1 (chain-pom-001): missing justification"));
+        assert!(error.contains("#2 (xml-pom-001): invalid uri glob"));
+        assert!(error.contains("#2 (xml-pom-001): invalid message regex"));
+        assert!(error.contains("#3 (xml-pom-001): missing uri or fingerprint"));
+    }
+}
@@ -0,0 +1,152 @@
+use std::fs;
+use std::path::Path;
+use serde::{Deserialize, Serialize};
+use crate::yaml_parser::Incident;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TriageStatus {
+    FalsePositive,
+    WontFix,
+    Todo,
+}
+
+impl TriageStatus {
+    pub fn label(&self) -> &'static str {
+        match self {
+            TriageStatus::FalsePositive => "false positive",
+            TriageStatus::WontFix => "won't fix",
+            TriageStatus::Todo => "todo",
+        }
+    }
+}
+
+/// Triage decision for a single incident.
+///
+/// Entries with a `fingerprint` match on it, so decisions survive the
+/// incident moving to another line. Older entries without one, and every
+/// entry when no fingerprint is at hand because no `source_root` is
+/// configured, match on the URI, line and message.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct TriageEntry {
+    pub rule_id: String,
+    pub uri: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line_number: Option<i32>,
+    pub message: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fingerprint: Option<String>,
+    pub status: TriageStatus,
+}
+
+impl TriageEntry {
+    fn matches(&self, rule_id: &str, incident: &Incident, fingerprint: Option<&str>) -> bool {
+        self.rule_id == rule_id
+            && match (self.fingerprint.as_deref(), fingerprint) {
+                (Some(stored), Some(fingerprint)) => stored == fingerprint,
+                _ => *self.uri == *incident.uri
+                    && self.line_number == incident.line_number
+                    && self.message == incident.message,
+            }
+    }
+}
+
+/// Suffix appended to a report path to name its triage sidecar.
+pub const SIDECAR_SUFFIX: &str = ".triage.yaml";
+
+/// Triage state of a report, kept in a YAML sidecar file next to it.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Triage {
+    pub entries: Vec<TriageEntry>,
+}
+
+impl Triage {
+
+    /// Returns the sidecar path used for `report_path`, e.g. `output.yaml.triage.yaml`.
+    pub fn sidecar_path(report_path: &str) -> String {
+        format!("{}{}", report_path, SIDECAR_SUFFIX)
+    }
+
+    /// Loads the sidecar at `path`, returning an empty state if it does not exist yet.
+    pub fn load(path: &str) -> Result<Triage, Box<dyn std::error::Error>> {
+        if !Path::new(path).exists() {
+            return Ok(Triage::default());
+        }
+        let contents = fs::read_to_string(path)?;
+        Ok(serde_yaml::from_str(&contents)?)
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), Box<dyn std::error::Error>> {
+        fs::write(path, serde_yaml::to_string(self)?)?;
+        Ok(())
+    }
+
+    /// Status of an incident, `fingerprint` being its entry in `Violation::fingerprints`.
+    ///
+    /// Pass `None` when no `source_root` is configured: fingerprints then
+    /// hash the absolute path and would not match once the sources move.
+    pub fn status(&self, rule_id: &str, incident: &Incident, fingerprint: Option<&str>) -> Option<TriageStatus> {
+        self.entries
+            .iter()
+            .find(|entry| entry.matches(rule_id, incident, fingerprint))
+            .map(|entry| entry.status)
+    }
+
+    /// Sets the status of an incident, `None` clears any previous decision.
+    /// The fingerprint, when given, is stored with the decision, see `status`.
+    pub fn set_status(&mut self, rule_id: &str, incident: &Incident, fingerprint: Option<&str>, status: Option<TriageStatus>) {
+        self.entries.retain(|entry| !entry.matches(rule_id, incident, fingerprint));
+        if let Some(status) = status {
+            self.entries.push(TriageEntry {
+                rule_id: rule_id.to_string(),
+                uri: incident.uri.to_string(),
+                line_number: incident.line_number,
+                message: incident.message.clone(),
+                fingerprint: fingerprint.map(str::to_string),
+                status,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::yaml_parser::Violation;
+
+    #[test]
+    fn set_save_and_load() {
+        let incident = Incident {
+            uri: "file:///app/pom.xml".into(),
+            message: "Replace dependency".to_string(),
+            line_number: Some(12),
+            ..Default::default()
+        };
+        let fingerprints = |incident: &Incident| Violation { incidents: vec![incident.clone()], ..Default::default() }
+            .fingerprints("xml-pom-001", Some("/app"));
+        let ids = fingerprints(&incident);
+        let fingerprint = Some(ids[0].as_str());
+        let mut triage = Triage::default();
+        triage.set_status("xml-pom-001", &incident, fingerprint, Some(TriageStatus::Todo));
+        triage.set_status("xml-pom-001", &incident, fingerprint, Some(TriageStatus::FalsePositive));
+        assert_eq!(triage.entries.len(), 1);
+        assert_eq!(triage.status("xml-pom-001", &incident, fingerprint), Some(TriageStatus::FalsePositive));
+        assert_eq!(triage.status("other-rule", &incident, fingerprint), None);
+
+        let moved = Incident { line_number: Some(15), ..incident.clone() };
+        assert_eq!(triage.status("xml-pom-001", &moved, Some(&fingerprints(&moved)[0])), Some(TriageStatus::FalsePositive));
+        // Without a fingerprint the stored location is compared instead.
+        assert_eq!(triage.status("xml-pom-001", &incident, None), Some(TriageStatus::FalsePositive));
+        assert_eq!(triage.status("xml-pom-001", &moved, None), None);
+
+        let path = std::env::temp_dir().join(format!("kai-triage-{}.yaml", std::process::id()));
+        let path = path.to_str().unwrap();
+        triage.save(path).unwrap();
+        assert_eq!(Triage::load(path).unwrap(), triage);
+        std::fs::remove_file(path).unwrap();
+
+        triage.set_status("xml-pom-001", &incident, fingerprint, None);
+        assert!(triage.entries.is_empty());
+    }
+}
@@ -0,0 +1,581 @@
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Modifier, Style, Stylize};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, List, ListItem, ListState, Paragraph, Wrap};
+use ratatui::Frame;
+use crate::config::Config;
+use crate::location::local_path;
+use crate::triage::{Triage, TriageStatus};
+use crate::yaml_parser::{AnalysisReport, Incident};
+
+/// Top level grouping of the browser.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Mode {
+    /// rulesets -> violations -> incidents
+    Rulesets,
+    /// files -> violations -> incidents
+    Files,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Level {
+    Groups,
+    Violations,
+    Incidents,
+    Detail,
+}
+
+// (ruleset, rule id) of a violation.
+type RuleKey = (Arc<str>, Arc<str>);
+
+// One incident together with the violation metadata it belongs to.
+#[derive(Clone, Debug)]
+struct IncidentRow {
+    ruleset: Arc<str>,
+    rule_id: Arc<str>,
+    description: String,
+    category: Option<String>,
+    labels: Vec<Arc<str>>,
+    incident: Incident,
+    // Only with a configured `source_root`, see `Triage::status`.
+    fingerprint: Option<String>,
+}
+
+/// State of the terminal browser, kept separate from the terminal so it can be driven in tests.
+pub struct App {
+    all: Vec<IncidentRow>,
+    rows: Vec<IncidentRow>,
+    categories: Vec<String>,
+    pub mode: Mode,
+    pub level: Level,
+    group: Option<String>,
+    rule: Option<RuleKey>,
+    incident: Option<usize>,
+    pub selected: usize,
+    stack: Vec<usize>,
+    pub category_filter: Option<String>,
+    pub label_filter: Option<String>,
+    label_input: Option<String>,
+    pub triage: Triage,
+    triage_path: Option<String>,
+    uri_prefixes: BTreeMap<String, String>,
+    pub should_quit: bool,
+    status: String,
+}
+
+impl App {
+
+    /// Creates the browser for `report`; triage changes are written to `triage_path` when given.
+    ///
+    /// Locations are shown as local paths when `config` maps their URI prefix,
+    /// and fingerprints, which triage decisions are keyed on, are taken
+    /// relative to its `source_root`. Without one, decisions are keyed on the
+    /// incident location and no fingerprint is shown.
+    pub fn new(report: &AnalysisReport, triage: Triage, triage_path: Option<String>, config: &Config) -> App {
+        let mut all = Vec::new();
+        for ruleset in &report.rulesets {
+            for (rule_id, violation) in &ruleset.violations {
+                let fingerprints = config.source_root.as_deref().map(|root| violation.fingerprints(rule_id, Some(root)));
+                for (index, incident) in violation.incidents.iter().enumerate() {
+                    all.push(IncidentRow {
+                        ruleset: ruleset.name.clone(),
+                        rule_id: rule_id.clone(),
+                        description: violation.description.clone(),
+                        category: violation.category.clone(),
+                        labels: violation.labels.clone(),
+                        incident: incident.clone(),
+                        fingerprint: fingerprints.as_ref().map(|fingerprints| fingerprints[index].clone()),
+                    });
+                }
+            }
+        }
+        all.sort_by(|a, b| {
+            (&a.ruleset, &a.rule_id, &a.incident.uri, a.incident.line_number)
+                .cmp(&(&b.ruleset, &b.rule_id, &b.incident.uri, b.incident.line_number))
+        });
+        let mut categories: Vec<String> = all.iter().filter_map(|row| row.category.clone()).collect();
+        categories.sort();
+        categories.dedup();
+
+        App {
+            rows: all.clone(),
+            all,
+            categories,
+            mode: Mode::Rulesets,
+            level: Level::Groups,
+            group: None,
+            rule: None,
+            incident: None,
+            selected: 0,
+            stack: Vec::new(),
+            category_filter: None,
+            label_filter: None,
+            label_input: None,
+            triage,
+            triage_path,
+            uri_prefixes: config.uri_prefixes.clone(),
+            should_quit: false,
+            status: String::new(),
+        }
+    }
+
+    fn group_key<'a>(&self, row: &'a IncidentRow) -> &'a str {
+        match self.mode {
+            Mode::Rulesets => &row.ruleset,
+            Mode::Files => &row.incident.uri,
+        }
+    }
+
+    /// Groups of the top level with their incident counts.
+    fn groups(&self) -> Vec<(String, usize)> {
+        let mut groups = BTreeMap::<String, usize>::new();
+        for row in &self.rows {
+            *groups.entry(self.group_key(row).to_string()).or_default() += 1;
+        }
+        groups.into_iter().collect()
+    }
+
+    /// (ruleset, rule id) pairs in the selected group with their incident counts.
+    fn violations(&self) -> Vec<(RuleKey, usize)> {
+        let mut violations = BTreeMap::<RuleKey, usize>::new();
+        for row in &self.rows {
+            if Some(self.group_key(row)) == self.group.as_deref() {
+                *violations.entry((row.ruleset.clone(), row.rule_id.clone())).or_default() += 1;
+            }
+        }
+        violations.into_iter().collect()
+    }
+
+    /// Indexes into `rows` of the incidents of the selected violation.
+    fn incidents(&self) -> Vec<usize> {
+        self.rows
+            .iter()
+            .enumerate()
+            .filter(|(_, row)| {
+                Some(self.group_key(row)) == self.group.as_deref()
+                    && self.rule.as_ref() == Some(&(row.ruleset.clone(), row.rule_id.clone()))
+            })
+            .map(|(index, _)| index)
+            .collect()
+    }
+
+    fn item_count(&self) -> usize {
+        match self.level {
+            Level::Groups => self.groups().len(),
+            Level::Violations => self.violations().len(),
+            Level::Incidents => self.incidents().len(),
+            Level::Detail => 0,
+        }
+    }
+
+    fn enter(&mut self) {
+        let next = match self.level {
+            Level::Groups => {
+                self.group = self.groups().get(self.selected).map(|(group, _)| group.clone());
+                self.group.as_ref().map(|_| Level::Violations)
+            }
+            Level::Violations => {
+                self.rule = self.violations().get(self.selected).map(|(rule, _)| rule.clone());
+                self.rule.as_ref().map(|_| Level::Incidents)
+            }
+            Level::Incidents => {
+                self.incident = self.incidents().get(self.selected).copied();
+                self.incident.map(|_| Level::Detail)
+            }
+            Level::Detail => None,
+        };
+        if let Some(level) = next {
+            self.stack.push(self.selected);
+            self.level = level;
+            self.selected = 0;
+        }
+    }
+
+    fn back(&mut self) {
+        self.level = match self.level {
+            Level::Groups => return,
+            Level::Violations => Level::Groups,
+            Level::Incidents => Level::Violations,
+            Level::Detail => Level::Incidents,
+        };
+        self.selected = self.stack.pop().unwrap_or(0);
+    }
+
+    fn reset_navigation(&mut self) {
+        self.level = Level::Groups;
+        self.group = None;
+        self.rule = None;
+        self.incident = None;
+        self.selected = 0;
+        self.stack.clear();
+    }
+
+    fn apply_filters(&mut self) {
+        self.rows = self.all
+            .iter()
+            .filter(|row| match &self.category_filter {
+                Some(category) => row.category.as_ref() == Some(category),
+                None => true,
+            })
+            .filter(|row| match &self.label_filter {
+                Some(label) => row.labels.iter().any(|l| l.contains(label.as_str())),
+                None => true,
+            })
+            .cloned()
+            .collect();
+        self.reset_navigation();
+    }
+
+    fn cycle_category(&mut self) {
+        let position = self.category_filter
+            .as_ref()
+            .and_then(|current| self.categories.iter().position(|c| c == current));
+        self.category_filter = match position {
+            None => self.categories.first().cloned(),
+            Some(index) => self.categories.get(index + 1).cloned(),
+        };
+        self.apply_filters();
+    }
+
+    fn current_incident(&self) -> Option<usize> {
+        match self.level {
+            Level::Incidents => self.incidents().get(self.selected).copied(),
+            Level::Detail => self.incident,
+            _ => None,
+        }
+    }
+
+    fn set_triage(&mut self, status: Option<TriageStatus>) -> Result<(), Box<dyn std::error::Error>> {
+        let Some(index) = self.current_incident() else {
+            return Ok(());
+        };
+        let row = &self.rows[index];
+        self.triage.set_status(&row.rule_id, &row.incident, row.fingerprint.as_deref(), status);
+        if let Some(path) = &self.triage_path {
+            self.triage.save(path)?;
+        }
+        self.status = match status {
+            Some(status) => format!("Marked as {}", status.label()),
+            None => "Cleared triage".to_string(),
+        };
+        Ok(())
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) -> Result<(), Box<dyn std::error::Error>> {
+        if let Some(input) = self.label_input.as_mut() {
+            match key.code {
+                KeyCode::Char(c) => input.push(c),
+                KeyCode::Backspace => {
+                    input.pop();
+                }
+                KeyCode::Enter => {
+                    let label = self.label_input.take().unwrap_or_default();
+                    self.label_filter = if label.is_empty() { None } else { Some(label) };
+                    self.apply_filters();
+                }
+                KeyCode::Esc => self.label_input = None,
+                _ => {}
+            }
+            return Ok(());
+        }
+
+        self.status.clear();
+        match key.code {
+            KeyCode::Char('q') => self.should_quit = true,
+            KeyCode::Down | KeyCode::Char('j') if self.selected + 1 < self.item_count() => self.selected += 1,
+            KeyCode::Up | KeyCode::Char('k') => self.selected = self.selected.saturating_sub(1),
+            KeyCode::Enter | KeyCode::Right | KeyCode::Char('l') => self.enter(),
+            KeyCode::Esc | KeyCode::Left | KeyCode::Char('h') | KeyCode::Backspace => self.back(),
+            KeyCode::Tab => {
+                self.mode = match self.mode {
+                    Mode::Rulesets => Mode::Files,
+                    Mode::Files => Mode::Rulesets,
+                };
+                self.reset_navigation();
+            }
+            KeyCode::Char('c') => self.cycle_category(),
+            KeyCode::Char('/') => self.label_input = Some(String::new()),
+            KeyCode::Char('f') => self.set_triage(Some(TriageStatus::FalsePositive))?,
+            KeyCode::Char('w') => self.set_triage(Some(TriageStatus::WontFix))?,
+            KeyCode::Char('t') => self.set_triage(Some(TriageStatus::Todo))?,
+            KeyCode::Char('u') => self.set_triage(None)?,
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn triage_marker(&self, row: &IncidentRow) -> &'static str {
+        match self.triage.status(&row.rule_id, &row.incident, row.fingerprint.as_deref()) {
+            Some(TriageStatus::FalsePositive) => "[FP] ",
+            Some(TriageStatus::WontFix) => "[WF] ",
+            Some(TriageStatus::Todo) => "[TODO] ",
+            None => "",
+        }
+    }
+
+    fn list_items(&self) -> Vec<ListItem<'static>> {
+        match self.level {
+            Level::Groups => self.groups()
+                .into_iter()
+                .map(|(group, count)| ListItem::new(format!("{} ({})", group, count)))
+                .collect(),
+            Level::Violations => self.violations()
+                .into_iter()
+                .map(|((ruleset, rule_id), count)| match self.mode {
+                    Mode::Rulesets => ListItem::new(format!("{} ({})", rule_id, count)),
+                    Mode::Files => ListItem::new(format!("{} / {} ({})", ruleset, rule_id, count)),
+                })
+                .collect(),
+            Level::Incidents => self.incidents()
+                .into_iter()
+                .map(|index| {
+                    let row = &self.rows[index];
+                    let line = row.incident.line_number.map(|l| l.to_string()).unwrap_or_else(|| "-".to_string());
+                    let location = match self.mode {
+                        Mode::Rulesets => format!("{}:{}", row.incident.uri, line),
+                        Mode::Files => format!("line {}", line),
+                    };
+                    let message = row.incident.message.lines().next().unwrap_or_default().to_string();
+                    ListItem::new(format!("{}{}  {}", self.triage_marker(row), location, message))
+                })
+                .collect(),
+            Level::Detail => Vec::new(),
+        }
+    }
+
+    fn detail_lines(&self, row: &IncidentRow) -> Vec<Line<'static>> {
+        let mut lines = vec![
+            Line::from(vec![Span::from("Rule: ").bold(), Span::from(format!("{} / {}", row.ruleset, row.rule_id))]),
+            Line::from(vec![Span::from("Category: ").bold(), Span::from(row.category.clone().unwrap_or_default())]),
+            Line::from(vec![Span::from("Labels: ").bold(), Span::from(row.labels.join(", "))]),
+            Line::from(vec![
+                Span::from("Location: ").bold(),
+                Span::from(format!(
+                    "{}:{}",
+                    local_path(&self.uri_prefixes, &row.incident.uri).unwrap_or_else(|| row.incident.uri.to_string()),
+                    row.incident.line_number.unwrap_or_default(),
+                )),
+            ]),
+            Line::from(vec![Span::from("Fingerprint: ").bold(), Span::from(row.fingerprint.clone().unwrap_or_else(|| "-".to_string()))]),
+            Line::from(vec![
+                Span::from("Triage: ").bold(),
+                Span::from(self.triage.status(&row.rule_id, &row.incident, row.fingerprint.as_deref()).map(|s| s.label()).unwrap_or("-")),
+            ]),
+            Line::from(""),
+        ];
+        if !row.description.is_empty() {
+            lines.extend(row.description.lines().map(|l| Line::from(l.to_string())));
+            lines.push(Line::from(""));
+        }
+        lines.extend(row.incident.message.lines().map(|l| Line::from(l.to_string())));
+        lines.push(Line::from(""));
+
+        if let Some(snippet) = row.incident.snippet() {
+            for text in snippet.render().lines() {
+                let line = Line::from(text.to_string());
+                if text.starts_with('>') {
+                    lines.push(line.style(Style::default().add_modifier(Modifier::REVERSED | Modifier::BOLD)));
+                } else {
+                    lines.push(line);
+                }
+            }
+        }
+        lines
+    }
+
+    fn title(&self) -> String {
+        let mut parts = vec![match self.mode {
+            Mode::Rulesets => "Rulesets".to_string(),
+            Mode::Files => "Files".to_string(),
+        }];
+        if let Some(group) = &self.group {
+            if self.level != Level::Groups {
+                parts.push(group.clone());
+            }
+        }
+        if let Some((_, rule_id)) = &self.rule {
+            if matches!(self.level, Level::Incidents | Level::Detail) {
+                parts.push(rule_id.to_string());
+            }
+        }
+        parts.join(" > ")
+    }
+
+    pub fn draw(&self, frame: &mut Frame) {
+        let [header, body, footer] = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Min(1),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+
+        let filters = format!(
+            "category: {}  label: {}  incidents: {}",
+            self.category_filter.as_deref().unwrap_or("*"),
+            self.label_filter.as_deref().unwrap_or("*"),
+            self.rows.len(),
+        );
+        frame.render_widget(Paragraph::new(filters), header);
+
+        let block = Block::bordered().title(self.title());
+        match (self.level, self.incident) {
+            (Level::Detail, Some(index)) => {
+                let detail = Paragraph::new(self.detail_lines(&self.rows[index]))
+                    .block(block)
+                    .wrap(Wrap { trim: false });
+                frame.render_widget(detail, body);
+            }
+            _ => {
+                let list = List::new(self.list_items())
+                    .block(block)
+                    .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+                let mut state = ListState::default().with_selected(Some(self.selected));
+                frame.render_stateful_widget(list, body, &mut state);
+            }
+        }
+
+        let help = match &self.label_input {
+            Some(input) => format!("label filter: {}_", input),
+            None if !self.status.is_empty() => self.status.clone(),
+            None => "q quit  enter open  esc back  tab rulesets/files  c category  / label  f/w/t/u triage".to_string(),
+        };
+        frame.render_widget(Paragraph::new(help), footer);
+    }
+}
+
+/// Opens the interactive browser for `report`, as loaded from `report_path`
+/// and filtered by the caller.
+///
+/// Triage decisions are loaded from and saved to `Triage::sidecar_path(report_path)`.
+pub fn run(report_path: &str, report: &AnalysisReport, config: &Config) -> Result<(), Box<dyn std::error::Error>> {
+    let triage_path = Triage::sidecar_path(report_path);
+    let triage = Triage::load(&triage_path)?;
+    let mut app = App::new(report, triage, Some(triage_path), config);
+
+    let mut terminal = ratatui::init();
+    let result = (|| -> Result<(), Box<dyn std::error::Error>> {
+        while !app.should_quit {
+            terminal.draw(|frame| app.draw(frame))?;
+            if let Event::Key(key) = event::read()? {
+                if key.kind == KeyEventKind::Press {
+                    app.handle_key(key)?;
+                }
+            }
+        }
+        Ok(())
+    })();
+    ratatui::restore();
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::yaml_parser::parse_yaml;
+    use ratatui::backend::TestBackend;
+    use ratatui::Terminal;
+
+    fn press(app: &mut App, code: KeyCode) {
+        app.handle_key(KeyEvent::from(code)).unwrap();
+    }
+
+    fn render(app: &App) -> String {
+        let mut terminal = Terminal::new(TestBackend::new(120, 40)).unwrap();
+        terminal.draw(|frame| app.draw(frame)).unwrap();
+        let buffer = terminal.backend().buffer();
+        buffer.content().iter().map(|cell| cell.symbol()).collect()
+    }
+
+    #[test]
+    fn navigate_files_to_snippet() {
+        let report = parse_yaml("samples/demo-output.yaml").unwrap();
+        let config = Config::parse("[uri_prefixes]\n\"file:///examples\" = \"/work/demo\"").unwrap();
+        let mut app = App::new(&report, Triage::default(), None, &config);
+
+        press(&mut app, KeyCode::Tab);
+        assert_eq!(app.mode, Mode::Files);
+        let position = app.groups().iter().position(|(uri, _)| uri == "file:///examples/customers-tomcat-legacy/pom.xml").unwrap();
+        for _ in 0..position {
+            press(&mut app, KeyCode::Down);
+        }
+        press(&mut app, KeyCode::Enter);
+        assert_eq!(app.level, Level::Violations);
+        assert_eq!(app.violations().len(), 2);
+
+        press(&mut app, KeyCode::Enter);
+        press(&mut app, KeyCode::Enter);
+        assert_eq!(app.level, Level::Detail);
+        let screen = render(&app);
+        assert!(screen.contains("chain-pom-001"));
+        assert!(screen.contains("<artifactId>"));
+        assert!(screen.contains("/work/demo/customers-tomcat-legacy/pom.xml"));
+
+        press(&mut app, KeyCode::Esc);
+        assert_eq!(app.level, Level::Incidents);
+    }
+
+    #[test]
+    fn category_and_label_filters() {
+        let report = parse_yaml("samples/demo-output.yaml").unwrap();
+        let mut app = App::new(&report, Triage::default(), None, &Config::default());
+        let total = app.rows.len();
+
+        press(&mut app, KeyCode::Char('c'));
+        assert_eq!(app.category_filter.as_deref(), Some("mandatory"));
+        assert_eq!(app.rows.len(), 4);
+
+        while app.category_filter.is_some() {
+            press(&mut app, KeyCode::Char('c'));
+        }
+        assert_eq!(app.rows.len(), total);
+
+        press(&mut app, KeyCode::Char('/'));
+        for c in "testing".chars() {
+            press(&mut app, KeyCode::Char(c));
+        }
+        press(&mut app, KeyCode::Enter);
+        assert_eq!(app.label_filter.as_deref(), Some("testing"));
+        assert!(!app.rows.is_empty() && app.rows.len() < total);
+        assert!(app.rows.iter().all(|row| row.labels.iter().any(|l| l.contains("testing"))));
+
+        press(&mut app, KeyCode::Char('/'));
+        press(&mut app, KeyCode::Enter);
+        assert_eq!(app.label_filter, None);
+        assert_eq!(app.rows.len(), total);
+    }
+
+    #[test]
+    fn triage_is_saved_to_sidecar() {
+        let report = parse_yaml("samples/demo-output.yaml").unwrap();
+        let path = std::env::temp_dir().join(format!("kai-tui-triage-{}.yaml", std::process::id()));
+        let path = path.to_str().unwrap().to_string();
+        let mut app = App::new(&report, Triage::default(), Some(path.clone()), &Config::default());
+
+        press(&mut app, KeyCode::Enter);
+        press(&mut app, KeyCode::Enter);
+        press(&mut app, KeyCode::Char('f'));
+        press(&mut app, KeyCode::Down);
+        press(&mut app, KeyCode::Char('w'));
+
+        let saved = Triage::load(&path).unwrap();
+        assert_eq!(saved.entries.len(), 2);
+        assert_eq!(saved.entries[0].status, TriageStatus::FalsePositive);
+        assert_eq!(saved.entries[1].status, TriageStatus::WontFix);
+        // Without a source root the decisions are keyed on the location only.
+        assert!(saved.entries.iter().all(|entry| entry.fingerprint.is_none()));
+        assert!(render(&app).contains("[FP]"));
+
+        let config = Config { source_root: Some("/examples".to_string()), ..Config::default() };
+        let mut app = App::new(&report, saved, Some(path.clone()), &config);
+        press(&mut app, KeyCode::Enter);
+        press(&mut app, KeyCode::Enter);
+        press(&mut app, KeyCode::Char('t'));
+        let saved = Triage::load(&path).unwrap();
+        assert_eq!(saved.entries.len(), 2);
+        assert!(saved.entries.iter().any(|entry| entry.status == TriageStatus::Todo && entry.fingerprint.is_some()));
+        std::fs::remove_file(&path).unwrap();
+    }
+}
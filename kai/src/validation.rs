@@ -0,0 +1,171 @@
+use std::fmt;
+use std::sync::Arc;
+use serde::Serialize;
+use crate::yaml_parser::AnalysisReport;
+
+/// Categories the analyzer assigns to violations.
+pub const KNOWN_CATEGORIES: [&str; 3] = ["mandatory", "optional", "potential"];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FindingKind {
+    UnnamedRuleset,
+    NoIncidents,
+    MissingEffort,
+    UnknownCategory,
+    EmptyUri,
+    NonFileUri,
+    InvalidLineNumber,
+}
+
+/// A semantic problem found in a report by `AnalysisReport::validate`.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Finding {
+    pub kind: FindingKind,
+    /// Position of the ruleset in the report, useful when it has no name.
+    pub ruleset_index: usize,
+    pub ruleset: Arc<str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rule_id: Option<Arc<str>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub uri: Option<Arc<str>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line_number: Option<i32>,
+    pub message: String,
+}
+
+impl fmt::Display for Finding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.ruleset.is_empty() {
+            write!(f, "ruleset #{}", self.ruleset_index)?;
+        } else {
+            write!(f, "{}", self.ruleset)?;
+        }
+        if let Some(rule_id) = &self.rule_id {
+            write!(f, "/{}", rule_id)?;
+        }
+        if let Some(uri) = &self.uri {
+            write!(f, " {}", uri)?;
+            if let Some(line) = self.line_number {
+                write!(f, ":{}", line)?;
+            }
+        }
+        write!(f, ": {}", self.message)
+    }
+}
+
+impl AnalysisReport {
+
+    /// Checks the report for semantic problems that the YAML schema alone does not catch.
+    ///
+    /// An empty result means the report can be imported.
+    pub fn validate(&self) -> Vec<Finding> {
+        let mut findings = Vec::new();
+        for (ruleset_index, ruleset) in self.rulesets.iter().enumerate() {
+            let finding = |kind, rule_id: Option<&Arc<str>>, uri: Option<&Arc<str>>, line_number, message: String| Finding {
+                kind,
+                ruleset_index,
+                ruleset: ruleset.name.clone(),
+                rule_id: rule_id.cloned(),
+                uri: uri.cloned(),
+                line_number,
+                message,
+            };
+
+            if ruleset.name.trim().is_empty() {
+                findings.push(finding(FindingKind::UnnamedRuleset, None, None, None, "ruleset has no name".to_string()));
+            }
+
+            for (rule_id, violation) in &ruleset.violations {
+                let rule = Some(rule_id);
+                if violation.incidents.is_empty() {
+                    findings.push(finding(FindingKind::NoIncidents, rule, None, None, "violation has no incidents".to_string()));
+                }
+                match violation.category.as_deref() {
+                    Some("mandatory") if violation.effort.is_none() => {
+                        findings.push(finding(FindingKind::MissingEffort, rule, None, None, "mandatory violation has no effort".to_string()));
+                    }
+                    Some(category) if !KNOWN_CATEGORIES.contains(&category) => {
+                        findings.push(finding(FindingKind::UnknownCategory, rule, None, None, format!("unknown category `{}`", category)));
+                    }
+                    _ => {}
+                }
+
+                for incident in &violation.incidents {
+                    let uri = Some(&incident.uri);
+                    if incident.uri.trim().is_empty() {
+                        findings.push(finding(FindingKind::EmptyUri, rule, None, incident.line_number, "incident has an empty uri".to_string()));
+                    } else if !incident.uri.starts_with("file://") {
+                        findings.push(finding(FindingKind::NonFileUri, rule, uri, incident.line_number, "incident uri is not a file:// uri".to_string()));
+                    }
+                    if let Some(line) = incident.line_number.filter(|line| *line <= 0) {
+                        findings.push(finding(FindingKind::InvalidLineNumber, rule, uri, Some(line), format!("invalid line number {}", line)));
+                    }
+                }
+            }
+        }
+        findings
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::yaml_parser::parse_yaml;
+
+    #[test]
+    fn validate_samples() {
+        assert_eq!(parse_yaml("samples/coolstore_analysis_output.yaml").unwrap().validate(), Vec::new());
+
+        let findings = parse_yaml("samples/demo-output.yaml").unwrap().validate();
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].kind, FindingKind::InvalidLineNumber);
+        assert_eq!(findings[0].rule_id.as_deref(), Some("java-pomxml-dependencies"));
+    }
+
+    #[test]
+    fn reports_every_kind_of_finding() {
+        let report: AnalysisReport = serde_yaml::from_str(r#"
+rulesets:
+- name: ""
+  violations:
+    empty-rule:
+      description: No incidents
+      category: optional
+      incidents: []
+- name: broken
+  violations:
+    rule-a:
+      description: Mandatory without effort
+      category: mandatory
+      incidents:
+      - uri: ""
+        message: empty
+      - uri: https://example.com/App.java
+        message: remote
+        lineNumber: 0
+    rule-b:
+      description: Odd category
+      category: critical
+      effort: 1
+      incidents:
+      - uri: file:///app/App.java
+        message: fine
+        lineNumber: 3
+"#).unwrap();
+
+        let findings = report.validate();
+        let kinds: Vec<FindingKind> = findings.iter().map(|f| f.kind).collect();
+        assert_eq!(kinds, vec![
+            FindingKind::UnnamedRuleset,
+            FindingKind::NoIncidents,
+            FindingKind::MissingEffort,
+            FindingKind::EmptyUri,
+            FindingKind::NonFileUri,
+            FindingKind::InvalidLineNumber,
+            FindingKind::UnknownCategory,
+        ]);
+        assert_eq!(findings[0].to_string(), "ruleset #0: ruleset has no name");
+        assert_eq!(findings[5].to_string(), "broken/rule-a https://example.com/App.java:0: invalid line number 0");
+    }
+}
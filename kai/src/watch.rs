@@ -0,0 +1,180 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+use notify::{RecursiveMode, Watcher};
+use crate::config::Config;
+use crate::diff::diff_reports;
+use crate::suppression::Suppressions;
+use crate::yaml_parser::AnalysisReport;
+
+// Shortest wait for events, so a zero `delay` does not spin.
+const MIN_POLL: Duration = Duration::from_millis(10);
+
+/// Query re-run every time a watched report changes.
+#[derive(Clone, Debug, PartialEq)]
+pub enum WatchQuery {
+    /// Totals of the report.
+    Summary,
+    /// Impacted file names.
+    Files,
+    /// Totals of the violations carrying a label.
+    Label(String),
+}
+
+impl WatchQuery {
+    pub fn run(&self, report: &AnalysisReport) -> String {
+        match self {
+            WatchQuery::Summary => {
+                let summary = report.summary();
+                format!("{} incident(s) in {} file(s), effort {}", summary.incidents, summary.impacted_files, summary.effort)
+            }
+            WatchQuery::Files => report.impacted_file_names().join("\n"),
+            WatchQuery::Label(label) => {
+                let summary = report.filter_by_label(label).summary();
+                format!("{}: {} incident(s) in {} file(s), effort {}", label, summary.incidents, summary.impacted_files, summary.effort)
+            }
+        }
+    }
+}
+
+/// Collects change events and releases a path once it has been quiet for `delay`.
+///
+/// The analyzer writes large reports in several steps, waiting for the events
+/// to settle avoids parsing a half-written file.
+#[derive(Debug)]
+pub struct Debouncer {
+    delay: Duration,
+    pending: BTreeMap<PathBuf, Instant>,
+}
+
+impl Debouncer {
+    pub fn new(delay: Duration) -> Debouncer {
+        Debouncer { delay, pending: BTreeMap::new() }
+    }
+
+    /// Records a change of `path` seen at `now`.
+    pub fn touch(&mut self, path: PathBuf, now: Instant) {
+        self.pending.insert(path, now);
+    }
+
+    /// Returns the paths that have not changed for `delay`, forgetting them.
+    pub fn ready(&mut self, now: Instant) -> Vec<PathBuf> {
+        let ready: Vec<PathBuf> = self.pending
+            .iter()
+            .filter(|(_, last)| now.duration_since(**last) >= self.delay)
+            .map(|(path, _)| path.clone())
+            .collect();
+        for path in &ready {
+            self.pending.remove(path);
+        }
+        ready
+    }
+}
+
+/// Describes what changed between two versions of a report, see `diff_reports`.
+pub fn change_summary(previous: &AnalysisReport, current: &AnalysisReport, source_root: Option<&str>) -> String {
+    let diff = diff_reports(previous, current, source_root);
+    let mut out = format!("{} new, {} resolved, {} unchanged incident(s)", diff.new.len(), diff.resolved.len(), diff.unchanged);
+    for entry in &diff.new {
+        let _ = write!(out, "\n  + {} {}:{}", entry.rule_id, entry.incident.uri, entry.incident.line_number.unwrap_or_default());
+    }
+    for entry in &diff.resolved {
+        let _ = write!(out, "\n  - {} {}:{}", entry.rule_id, entry.incident.uri, entry.incident.line_number.unwrap_or_default());
+    }
+    out
+}
+
+// Loads `path` and prints the query result and the changes against the last good version.
+fn reload(path: &Path, query: &WatchQuery, config: &Config, suppressions: Option<&Suppressions>, reports: &mut BTreeMap<PathBuf, AnalysisReport>) {
+    let name = path.display();
+    let loaded = config.load_suppressed(&path.to_string_lossy(), suppressions).map(|result| result.report);
+    match loaded {
+        Ok(report) => {
+            println!("== {}", name);
+            println!("{}", query.run(&report));
+            if let Some(previous) = reports.get(path) {
+                println!("{}", change_summary(previous, &report, config.source_root.as_deref()));
+            }
+            reports.insert(path.to_path_buf(), report);
+        }
+        // Most likely a partial write, the next event will retry.
+        Err(e) => eprintln!("{}: not reloaded, keeping the previous version: {}", name, e),
+    }
+}
+
+/// Watches `paths` and re-runs `query` each time one of them changes.
+///
+/// Runs until the process is stopped. The parent directories are watched so
+/// reports replaced by a rename are picked up as well. Reports are loaded
+/// with `Config::load_suppressed`, so the configured selection applies, and
+/// `suppressions` are removed from every version.
+pub fn watch(paths: &[String], query: &WatchQuery, delay: Duration, config: &Config, suppressions: Option<&Suppressions>) -> Result<(), Box<dyn std::error::Error>> {
+    let paths: Vec<PathBuf> = paths.iter().map(|p| Path::new(p).canonicalize()).collect::<Result<_, _>>()?;
+    let mut reports = BTreeMap::new();
+    for path in &paths {
+        reload(path, query, config, suppressions, &mut reports);
+    }
+
+    let (tx, rx) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(tx)?;
+    for path in &paths {
+        watcher.watch(path.parent().unwrap_or(Path::new(".")), RecursiveMode::NonRecursive)?;
+    }
+
+    let mut debouncer = Debouncer::new(delay);
+    loop {
+        match rx.recv_timeout((delay / 4).max(MIN_POLL)) {
+            // Reading the report raises access events, only react to writes.
+            Ok(Ok(event)) if !event.kind.is_access() => {
+                for changed in event.paths {
+                    if paths.contains(&changed) {
+                        debouncer.touch(changed, Instant::now());
+                    }
+                }
+            }
+            Ok(Ok(_)) => {}
+            Ok(Err(e)) => eprintln!("watch error: {}", e),
+            Err(mpsc::RecvTimeoutError::Timeout) => {}
+            Err(mpsc::RecvTimeoutError::Disconnected) => return Ok(()),
+        }
+        for path in debouncer.ready(Instant::now()) {
+            reload(&path, query, config, suppressions, &mut reports);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::yaml_parser::parse_yaml;
+
+    #[test]
+    fn debouncer_waits_for_quiet_period() {
+        let start = Instant::now();
+        let mut debouncer = Debouncer::new(Duration::from_millis(500));
+        let report = PathBuf::from("output.yaml");
+
+        debouncer.touch(report.clone(), start);
+        debouncer.touch(report.clone(), start + Duration::from_millis(300));
+        assert!(debouncer.ready(start + Duration::from_millis(600)).is_empty());
+        assert_eq!(debouncer.ready(start + Duration::from_millis(800)), vec![report]);
+        assert!(debouncer.ready(start + Duration::from_millis(2000)).is_empty());
+    }
+
+    #[test]
+    fn change_summary_lists_new_and_resolved() {
+        let previous = parse_yaml("samples/demo-output.yaml").unwrap();
+        let mut current = previous.clone();
+        current.rulesets[0].violations.get_mut("xml-pom-001").unwrap().incidents.truncate(15);
+
+        let summary = change_summary(&previous, &current, None);
+        let mut lines = summary.lines();
+        assert_eq!(lines.next(), Some("0 new, 10 resolved, 76 unchanged incident(s)"));
+        assert!(lines.all(|line| line.starts_with("  - xml-pom-001 file:///examples/")));
+
+        assert_eq!(WatchQuery::Summary.run(&previous), "86 incident(s) in 20 file(s), effort 100");
+        assert_eq!(WatchQuery::Files.run(&previous).lines().count(), 20);
+    }
+}
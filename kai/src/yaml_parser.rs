@@ -1,11 +1,10 @@
-use std::fs::File;
-use std::io::Read;
-use serde::Deserialize;
-use std::collections::HashMap;
-use std::collections::HashSet;
-use serde_json; 
-
-#[derive(Debug, Default, Deserialize)]
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::sync::Arc;
+
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
 #[serde(default)]
 pub struct AnalysisReport {
     pub rulesets: Vec<Ruleset>,
@@ -16,32 +15,32 @@ impl AnalysisReport {
     /// This method returns the impacted file names from the analysis report.
     ///
     /// # Returns
-    /// * A `HashMap<String, Vec<Ruleset>>` where the key is the uri and the value is a Vector of Rulsets. 
+    /// * A sorted `Vec<Arc<str>>` of the impacted uris.
     ///
-    pub fn impacted_file_names(&self) -> Vec<String> {
-        let mut uris = HashSet::new();
+    pub fn impacted_file_names(&self) -> Vec<Arc<str>> {
+        let mut uris = BTreeSet::new();
         for ruleset in &self.rulesets {
-            for (_violation_name, violation) in &ruleset.violations {
+            for violation in ruleset.violations.values() {
                 for incident in &violation.incidents {
                     uris.insert(incident.uri.clone());
                 }
             }
         }
-        let vec: Vec<String> = uris.into_iter().collect();
+        let vec: Vec<Arc<str>> = uris.into_iter().collect();
         vec
     }
 
    
     // Exploring an alternative implementation of impacted files, trying to avoid the 
     // outer loop of uris like in original implementation
-    pub fn impacted_files_ugly(&self) -> HashMap<String, HashMap<String, Ruleset>> {
+    pub fn impacted_files_ugly(&self) -> ImpactedFiles {
         // key: uri:
         //  key: ruleset_name
         //     violations:
         //       key: violation_name: 
         //           incidents:
         //             - incident data //stripped to just that uri
-        let mut impacted_files = HashMap::<String, HashMap<String, Ruleset>>::new();
+        let mut impacted_files = ImpactedFiles::new();
         for ruleset in &self.rulesets {
             for (violation_key, violation) in &ruleset.violations {
                 for incident in &violation.incidents {
@@ -67,10 +66,10 @@ impl AnalysisReport {
                             stripped_violation.incidents.push(incident.clone());
 
                             let mut stripped_ruleset = ruleset.clone();
-                            stripped_ruleset.violations = HashMap::new();
+                            stripped_ruleset.violations = BTreeMap::new();
                             stripped_ruleset.tags = Vec::new();
-                            stripped_ruleset.insights = HashMap::new();
-                            stripped_ruleset.errors = HashMap::new();
+                            stripped_ruleset.insights = BTreeMap::new();
+                            stripped_ruleset.errors = BTreeMap::new();
                             stripped_ruleset.unmatched = Vec::new();
                             stripped_ruleset.violations.insert(violation_key.clone(), stripped_violation);
 
@@ -83,14 +82,14 @@ impl AnalysisReport {
                         stripped_violation.incidents.push(incident.clone());
 
                         let mut stripped_ruleset = ruleset.clone();
-                        stripped_ruleset.violations = HashMap::new();
+                        stripped_ruleset.violations = BTreeMap::new();
                         stripped_ruleset.tags = Vec::new();
-                        stripped_ruleset.insights = HashMap::new();
-                        stripped_ruleset.errors = HashMap::new();
+                        stripped_ruleset.insights = BTreeMap::new();
+                        stripped_ruleset.errors = BTreeMap::new();
                         stripped_ruleset.unmatched = Vec::new();
                         stripped_ruleset.violations.insert(violation_key.clone(), stripped_violation);
                         
-                        let mut uri_rulesets = HashMap::<String, Ruleset>::new();
+                        let mut uri_rulesets = BTreeMap::<Arc<str>, Ruleset>::new();
                         uri_rulesets.insert(ruleset.name.clone(), stripped_ruleset);
                         impacted_files.insert(incident.uri.clone(), uri_rulesets);
                     } 
@@ -102,82 +101,180 @@ impl AnalysisReport {
     }
            
   
-    pub fn impacted_files(&self) -> HashMap<String, HashMap<String, Ruleset>> {
+    pub fn impacted_files(&self) -> ImpactedFiles {
         // key: uri:
         //  key: ruleset_name
         //     violations:
         //       key: violation_name: 
         //           incidents:
         //             - incident data //stripped to just that uri
-        let mut impacted_files = HashMap::<String, HashMap<String, Ruleset>>::new();
+        let mut impacted_files = ImpactedFiles::new();
         
         for ruleset in &self.rulesets {
-            for (violation_key, violation) in &ruleset.violations {
-                for incident in &violation.incidents {
+            index_ruleset(ruleset, &mut impacted_files);
+        }
+        impacted_files
+    }
 
-                    impacted_files
-                        .entry(incident.uri.clone()) // Entry for the URI
-                        .or_insert_with(HashMap::new) // Insert a new ruleset HashMap if missing
-                        .entry(ruleset.name.clone()) // Entry for the ruleset name
-                        .or_insert_with(|| {
-                            let mut stripped_ruleset = ruleset.clone();
-                            stripped_ruleset.violations = HashMap::new();
-                            stripped_ruleset.tags.clear();
-                            stripped_ruleset.insights.clear();
-                            stripped_ruleset.errors.clear();
-                            stripped_ruleset.unmatched.clear();
-                            stripped_ruleset
-                        })
-                        .violations
-                        .entry(violation_key.clone()) // Entry for the violation
-                        .or_insert_with(|| {
-                            let mut stripped_violation = violation.clone();
-                            stripped_violation.incidents = Vec::new();
-                            stripped_violation
-                        })
-                        .incidents
-                        .push(incident.clone()); // Add the incident to the stripped violation
+    /// Returns the number of incidents reported for each rule (violation name).
+    pub fn violation_counts(&self) -> BTreeMap<Arc<str>, usize> {
+        let mut counts = BTreeMap::<Arc<str>, usize>::new();
+        for ruleset in &self.rulesets {
+            for (violation_name, violation) in &ruleset.violations {
+                *counts.entry(violation_name.clone()).or_default() += violation.incidents.len();
+            }
+        }
+        counts
+    }
+
+    /// Returns a copy of the report that only keeps violations carrying `label`.
+    ///
+    /// Rulesets left without any matching violation are dropped.
+    pub fn filter_by_label(&self, label: &str) -> AnalysisReport {
+        let rulesets = self.rulesets
+            .iter()
+            .filter_map(|ruleset| {
+                let violations: BTreeMap<Arc<str>, Violation> = ruleset.violations
+                    .iter()
+                    .filter(|(_, violation)| violation.labels.iter().any(|l| &**l == label))
+                    .map(|(name, violation)| (name.clone(), violation.clone()))
+                    .collect();
+                if violations.is_empty() {
+                    return None;
                 }
+                let mut filtered = ruleset.clone();
+                filtered.violations = violations;
+                Some(filtered)
+            })
+            .collect();
+        AnalysisReport { rulesets }
+    }
+
+    /// Returns totals for the whole report.
+    pub fn summary(&self) -> ReportSummary {
+        let mut summary = ReportSummary {
+            rulesets: self.rulesets.len(),
+            impacted_files: self.impacted_file_names().len(),
+            ..Default::default()
+        };
+        for ruleset in &self.rulesets {
+            for violation in ruleset.violations.values() {
+                let incidents = violation.incidents.len();
+                summary.violations += 1;
+                summary.incidents += incidents;
+                summary.effort += violation.effort.unwrap_or(0) as i64 * incidents as i64;
+                let category = violation.category.clone().unwrap_or_else(|| "none".to_string());
+                *summary.incidents_by_category.entry(category).or_default() += incidents;
             }
         }
-        impacted_files
+        summary
     }
 
+    /// Loads an analyzer output, see `AnalysisReport::open` for the accepted inputs.
     pub fn load_from_file(&mut self, file_path: &str) -> Result<(), Box<dyn std::error::Error>> {
-        let mut file = File::open(file_path)?;
-        let mut contents = String::new();
-        file.read_to_string(&mut contents)?;
-    
-        let rulesets: Vec<Ruleset>= serde_yaml::from_str(&contents)?;
+        self.rulesets = AnalysisReport::open(file_path)?.rulesets;
+        Ok(())
+    }
+
+    /// Loads an analyzer output already in memory. Only uncompressed YAML is
+    /// accepted, use `AnalysisReport::from_reader` for the other inputs.
+    pub fn load_from_str(&mut self, contents: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let rulesets: Vec<Ruleset>= serde_yaml::from_str(contents)?;
         self.rulesets = rulesets;
+        self.sort_incidents();
+        self.intern();
         Ok(())
     }
+
+    /// Combines two reports, e.g. outputs of the analyzer run with different rulesets.
+    ///
+    /// Rulesets with the same name are merged: their violations and insights
+    /// are joined by rule ID with incidents concatenated and re-sorted.
+    /// Other rulesets of `other` are appended in order.
+    pub fn merge(&self, other: &AnalysisReport) -> AnalysisReport {
+        let mut merged = self.clone();
+        for ruleset in &other.rulesets {
+            let Some(target) = merged.rulesets.iter_mut().find(|r| r.name == ruleset.name) else {
+                merged.rulesets.push(ruleset.clone());
+                continue;
+            };
+            for (rule_id, violation) in &ruleset.violations {
+                match target.violations.get_mut(rule_id) {
+                    Some(existing) => {
+                        existing.incidents.extend(violation.incidents.iter().cloned());
+                        existing.incidents.sort_by(Incident::cmp_location);
+                    }
+                    None => {
+                        target.violations.insert(rule_id.clone(), violation.clone());
+                    }
+                }
+            }
+            for (rule_id, insight) in &ruleset.insights {
+                match target.insights.get_mut(rule_id) {
+                    Some(existing) => {
+                        existing.incidents.extend(insight.incidents.iter().cloned());
+                        existing.incidents.sort_by(Incident::cmp_location);
+                    }
+                    None => {
+                        target.insights.insert(rule_id.clone(), insight.clone());
+                    }
+                }
+            }
+            for tag in &ruleset.tags {
+                if !target.tags.contains(tag) {
+                    target.tags.push(tag.clone());
+                }
+            }
+            for rule_id in &ruleset.unmatched {
+                if !target.unmatched.contains(rule_id) {
+                    target.unmatched.push(rule_id.clone());
+                }
+            }
+            target.errors.extend(ruleset.errors.iter().map(|(k, v)| (k.clone(), v.clone())));
+        }
+        merged
+    }
+
+    /// Sorts the incidents of every violation and insight with `Incident::cmp_location`.
+    pub fn sort_incidents(&mut self) {
+        for ruleset in &mut self.rulesets {
+            for violation in ruleset.violations.values_mut() {
+                violation.incidents.sort_by(Incident::cmp_location);
+            }
+            for insight in ruleset.insights.values_mut() {
+                insight.incidents.sort_by(Incident::cmp_location);
+            }
+        }
+    }
 }
 
 
-#[derive(Clone, Debug, Default, Deserialize)]
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
 #[serde(default)]
 pub struct Ruleset {
-    pub name: String,
+    pub name: Arc<str>,
+
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub description: String,
 
     #[serde(skip_serializing_if = "Vec::is_empty")]
     pub tags: Vec<String>,
     
-    #[serde(skip_serializing_if = "HashMap::is_empty")]
-    pub violations: HashMap<String, Violation>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub violations: BTreeMap<Arc<str>, Violation>,
     
-    #[serde(skip_serializing_if = "HashMap::is_empty")]
-    pub insights: HashMap<String, Insight>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub insights: BTreeMap<Arc<str>, Insight>,
     
-    #[serde(skip_serializing_if = "HashMap::is_empty")]
-    pub errors: HashMap<String, String>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub errors: BTreeMap<String, String>,
     
     #[serde(skip_serializing_if = "Vec::is_empty")]
     pub unmatched: Vec<String>,
 }
 
 
-#[derive(Clone, Debug, Default, Deserialize)]
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
 #[serde(default)]
 pub struct Violation {
     pub description: String,
@@ -186,15 +283,25 @@ pub struct Violation {
     pub category: Option<String>,
     
     #[serde(skip_serializing_if = "Vec::is_empty")]
-    pub labels: Vec<String>,
+    pub labels: Vec<Arc<str>>,
     
     pub incidents: Vec<Incident>,
+
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub links: Vec<Link>,
     
     #[serde(skip_serializing_if = "Option::is_none")]
     pub effort: Option<i32>,
 }
 
-#[derive(Clone, Debug, Default, Deserialize)]
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Link {
+    pub url: String,
+    pub title: String,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
 #[serde(default)]
 pub struct Insight {
     pub description: String,
@@ -203,15 +310,15 @@ pub struct Insight {
     pub category: Option<String>,
     
     #[serde(skip_serializing_if = "Vec::is_empty")]
-    pub labels: Vec<String>,
+    pub labels: Vec<Arc<str>>,
     
     pub incidents: Vec<Incident>,
 }
 
-#[derive(Clone, Debug, Default, Deserialize)]
-#[serde(default)]
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+#[serde(default, rename_all = "camelCase")]
 pub struct Incident {
-    pub uri: String,
+    pub uri: Arc<str>,
     pub message: String,
     
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -220,13 +327,69 @@ pub struct Incident {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub line_number: Option<i32>,
     
-    #[serde(skip_serializing_if = "HashMap::is_empty")]
-    pub variables: HashMap<String, serde_json::Value>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub variables: BTreeMap<String, serde_json::Value>,
+}
+
+impl Incident {
+
+    /// The order used for incidents throughout kai: URI, then line, then message.
+    pub fn cmp_location(&self, other: &Incident) -> Ordering {
+        (&self.uri, self.line_number, &self.message).cmp(&(&other.uri, other.line_number, &other.message))
+    }
 }
 
-// Key: uri, Value: HashMap<String, Ruleset>
+// Adds the incidents of one ruleset to an impacted files index, see `AnalysisReport::impacted_files`.
+pub(crate) fn index_ruleset(ruleset: &Ruleset, impacted_files: &mut ImpactedFiles) {
+    for (violation_key, violation) in &ruleset.violations {
+        for incident in &violation.incidents {
+
+            impacted_files
+                .entry(incident.uri.clone()) // Entry for the URI
+                .or_default() // Insert a new ruleset BTreeMap if missing
+                .entry(ruleset.name.clone()) // Entry for the ruleset name
+                .or_insert_with(|| Ruleset {
+                    // Built from the scalar fields, cloning the ruleset would copy every incident.
+                    name: ruleset.name.clone(),
+                    description: ruleset.description.clone(),
+                    ..Ruleset::default()
+                })
+                .violations
+                .entry(violation_key.clone()) // Entry for the violation
+                .or_insert_with(|| Violation {
+                    description: violation.description.clone(),
+                    category: violation.category.clone(),
+                    labels: violation.labels.clone(),
+                    incidents: Vec::new(),
+                    links: violation.links.clone(),
+                    effort: violation.effort,
+                })
+                .incidents
+                .push(incident.clone()); // Add the incident to the stripped violation
+        }
+    }
+}
+
+/// Index returned by `AnalysisReport::impacted_files`: uri, then ruleset
+/// name, with each ruleset stripped to the violations and incidents of that uri.
+pub type ImpactedFiles = BTreeMap<Arc<str>, BTreeMap<Arc<str>, Ruleset>>;
+
+/// Totals returned by `AnalysisReport::summary`.
+///
+/// `effort` is the sum of each violation's effort multiplied by its number of incidents.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct ReportSummary {
+    pub rulesets: usize,
+    pub violations: usize,
+    pub incidents: usize,
+    pub impacted_files: usize,
+    pub effort: i64,
+    pub incidents_by_category: BTreeMap<String, usize>,
+}
+
+// Key: uri, Value: BTreeMap<String, Ruleset>
 //      Key: ruleset name, Value: Vec<Ruleset>  
-//type ImpactedRuleset = HashMap<String, HashMap<String, Ruleset>>;
+//type ImpactedRuleset = BTreeMap<Arc<str>, BTreeMap<Arc<str>, Ruleset>>;
 
 pub fn parse_yaml(file_path: &str) -> Result<AnalysisReport, Box<dyn std::error::Error>> {
     let mut report = AnalysisReport::default();
@@ -242,7 +405,7 @@ mod tests {
     fn test_coolstore_analysis() {
         let mut report = AnalysisReport::default();
         let result = report.load_from_file("samples/coolstore_analysis_output.yaml");
-        assert!(matches!(result, Ok(_))); 
+        assert!(result.is_ok()); 
         assert_eq!(report.rulesets.len(), 26, "The vector length did not match the expected value.");
         println!("Parsed report: {:?}", report);
     }
@@ -251,7 +414,7 @@ mod tests {
     fn test_demo_output_analysis() {
         let mut report = AnalysisReport::default();
         let result = report.load_from_file("samples/demo-output.yaml");
-        assert!(matches!(result, Ok(_)));
+        assert!(result.is_ok());
         assert_eq!(report.rulesets.len(), 1, "The vector length did not match the expected value.");
         println!("Parsed report: {:?}", report);
     }
@@ -283,7 +446,9 @@ mod tests {
                 "file:///examples/customers-tomcat-legacy/Dockerfile", 
                 "file:///examples/java/pom.xml", 
                 "file:///examples/builtin/inclusion_tests/dir-0/inclusion-test.json"];
-        assert_eq!(impacted_files.sort(), expected_impacted_files.sort());
+        impacted_files.sort();
+        expected_impacted_files.sort();
+        assert_eq!(impacted_files.iter().map(|uri| &**uri).collect::<Vec<&str>>(), expected_impacted_files);
     }
     
     #[test]
@@ -330,5 +495,44 @@ mod tests {
     }
 
 
+    #[test]
+    fn incident_fields_are_camel_case() {
+        let mut report = AnalysisReport::default();
+        report.load_from_str("
+- name: example
+  violations:
+    rule-001:
+      description: Example
+      incidents:
+      - uri: file:///app/pom.xml
+        message: Found it
+        codeSnip: \" 7  <dependency>\"
+        lineNumber: 7
+").unwrap();
+        let incident = &report.rulesets[0].violations["rule-001"].incidents[0];
+        assert_eq!(incident.line_number, Some(7));
+        assert_eq!(incident.code_snip.as_deref(), Some(" 7  <dependency>"));
+    }
+
+    #[test]
+    fn deterministic_order() {
+        let report = parse_yaml("samples/demo-output.yaml").unwrap();
+        let again = parse_yaml("samples/demo-output.yaml").unwrap();
+        assert_eq!(serde_yaml::to_string(&report).unwrap(), serde_yaml::to_string(&again).unwrap());
+
+        for violation in report.rulesets[0].violations.values() {
+            assert!(violation.incidents.windows(2).all(|w| w[0].cmp_location(&w[1]) != Ordering::Greater));
+        }
+        let violation = &report.rulesets[0].violations["chain-pom-001"];
+        let first = &violation.incidents[0];
+        assert_eq!(&*first.uri, "file:///examples/customers-tomcat-legacy/pom.xml");
+        assert_eq!(first.line_number, Some(45));
+
+        let uris: Vec<Arc<str>> = report.impacted_files().into_keys().collect();
+        assert_eq!(uris, report.impacted_file_names());
+        assert!(uris.windows(2).all(|w| w[0] < w[1]));
+    }
+
+
 
 }
\ No newline at end of file
@@ -0,0 +1,140 @@
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use proptest::prelude::*;
+use kai::yaml_parser::{AnalysisReport, Incident, Insight, Link, Ruleset, Violation};
+
+// Generators for reports shaped like analyzer output. Names are drawn from
+// small pools so that uris and rule IDs collide across rulesets.
+
+fn word() -> impl Strategy<Value = String> {
+    "[a-z][a-z0-9-]{0,8}"
+}
+
+fn symbol() -> impl Strategy<Value = Arc<str>> {
+    word().prop_map(Arc::from)
+}
+
+fn text() -> impl Strategy<Value = String> {
+    "[ -~\n\t]{0,40}"
+}
+
+fn uri() -> impl Strategy<Value = Arc<str>> {
+    prop_oneof![
+        prop::sample::select(vec!["pom.xml", "src/main/java/App.java", "src/main/resources/application.properties", "go.mod"])
+            .prop_map(|path| Arc::from(format!("file:///examples/app/{}", path))),
+        Just(Arc::from("")),
+    ]
+}
+
+fn incident() -> impl Strategy<Value = Incident> {
+    (uri(), text(), prop::option::of("[1-9][0-9]{0,2}  [ -~]{0,20}"), prop::option::of(0..500i32),
+        prop::collection::btree_map(word(), word().prop_map(serde_json::Value::String), 0..3))
+        .prop_map(|(uri, message, code_snip, line_number, variables)| Incident { uri, message, code_snip, line_number, variables })
+}
+
+fn incidents() -> impl Strategy<Value = Vec<Incident>> {
+    prop::collection::vec(incident(), 0..6).prop_map(|mut incidents| {
+        // The loader sorts incidents, so generated reports are sorted too.
+        incidents.sort_by(Incident::cmp_location);
+        incidents
+    })
+}
+
+fn violation() -> impl Strategy<Value = Violation> {
+    (text(), prop::option::of(prop::sample::select(vec!["mandatory", "optional", "potential"])),
+        prop::collection::vec(symbol(), 0..3), incidents(), prop::collection::vec((word(), text()), 0..2), prop::option::of(0..13i32))
+        .prop_map(|(description, category, labels, incidents, links, effort)| Violation {
+            description,
+            category: category.map(str::to_string),
+            labels,
+            incidents,
+            links: links.into_iter().map(|(url, title)| Link { url: format!("https://{}", url), title }).collect(),
+            effort,
+        })
+}
+
+fn insight() -> impl Strategy<Value = Insight> {
+    (text(), prop::collection::vec(symbol(), 0..3), incidents())
+        .prop_map(|(description, labels, incidents)| Insight { description, labels, incidents, ..Default::default() })
+}
+
+fn rule_id() -> impl Strategy<Value = Arc<str>> {
+    prop::sample::select(vec!["javax-to-jakarta-00001", "javax-to-jakarta-00002", "ejb-remote-00000", "jni-native-code-00000"])
+        .prop_map(Arc::from)
+}
+
+fn ruleset() -> impl Strategy<Value = Ruleset> {
+    (symbol(), text(), prop::collection::vec(word(), 0..3),
+        prop::collection::btree_map(rule_id(), violation(), 0..4),
+        prop::collection::btree_map(rule_id(), insight(), 0..2),
+        prop::collection::btree_map(word(), text(), 0..2),
+        prop::collection::vec(rule_id().prop_map(|id| id.to_string()), 0..2))
+        .prop_map(|(name, description, tags, violations, insights, errors, unmatched)| Ruleset {
+            name, description, tags, violations, insights, errors, unmatched,
+        })
+}
+
+fn report() -> impl Strategy<Value = AnalysisReport> {
+    prop::collection::vec(ruleset(), 0..4).prop_map(|mut rulesets| {
+        // Analyzer output never repeats a ruleset name.
+        let mut seen = std::collections::BTreeSet::new();
+        rulesets.retain(|r| seen.insert(r.name.clone()));
+        AnalysisReport { rulesets }
+    })
+}
+
+fn incident_count(report: &AnalysisReport) -> usize {
+    report.rulesets.iter().flat_map(|r| r.violations.values()).map(|v| v.incidents.len()).sum()
+}
+
+proptest! {
+    #[test]
+    fn serialize_then_parse_round_trips(report in report()) {
+        let yaml = serde_yaml::to_string(&report.rulesets).unwrap();
+        let mut parsed = AnalysisReport::default();
+        parsed.load_from_str(&yaml).unwrap();
+        prop_assert_eq!(parsed, report);
+    }
+
+    #[test]
+    fn impacted_files_keep_every_incident(report in report()) {
+        let impacted_files = report.impacted_files();
+        let indexed: usize = impacted_files.values()
+            .flat_map(|rulesets| rulesets.values())
+            .flat_map(|ruleset| ruleset.violations.values())
+            .map(|violation| violation.incidents.len())
+            .sum();
+        prop_assert_eq!(indexed, incident_count(&report));
+    }
+
+    #[test]
+    fn impacted_file_names_are_the_index_keys(report in report()) {
+        let keys: Vec<Arc<str>> = report.impacted_files().into_keys().collect();
+        prop_assert_eq!(report.impacted_file_names(), keys);
+    }
+
+    #[test]
+    fn merging_with_empty_is_identity(report in report()) {
+        let empty = AnalysisReport::default();
+        prop_assert_eq!(&report.merge(&empty), &report);
+        prop_assert_eq!(&empty.merge(&report), &report);
+    }
+
+    #[test]
+    fn merging_keeps_every_incident(a in report(), b in report()) {
+        let merged = a.merge(&b);
+        prop_assert_eq!(incident_count(&merged), incident_count(&a) + incident_count(&b));
+        let mut counts: BTreeMap<Arc<str>, usize> = a.violation_counts();
+        for (rule_id, count) in b.violation_counts() {
+            *counts.entry(rule_id).or_default() += count;
+        }
+        prop_assert_eq!(merged.violation_counts(), counts);
+    }
+
+    // Quick stand-in for the fuzz target in fuzz/, so malformed input is
+    // exercised on every test run.
+    #[test]
+    fn malformed_yaml_is_an_error_not_a_panic(input in "[-:\\[\\]{}|>'\"#&*!%@` \n\ta-z0-9]{0,200}") {
+        let _ = AnalysisReport::default().load_from_str(&input);
+    }
+}
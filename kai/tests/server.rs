@@ -0,0 +1,128 @@
+use std::collections::BTreeMap;
+use std::sync::{Arc, OnceLock};
+use axum::body::Body;
+use axum::http::{Request, StatusCode};
+use http_body_util::BodyExt;
+use kai::export::incident_records;
+use kai::related::{RelatedFile, Relation};
+use kai::server::{router, RuleEntry};
+use kai::yaml_parser::{parse_yaml, AnalysisReport, Ruleset};
+use tower::ServiceExt;
+
+// The samples, parsed once for every test.
+fn reports() -> &'static Arc<BTreeMap<String, AnalysisReport>> {
+    static REPORTS: OnceLock<Arc<BTreeMap<String, AnalysisReport>>> = OnceLock::new();
+    REPORTS.get_or_init(|| {
+        let mut reports = BTreeMap::new();
+        reports.insert("demo".to_string(), parse_yaml("samples/demo-output.yaml").unwrap());
+        reports.insert("coolstore".to_string(), parse_yaml("samples/coolstore_analysis_output.yaml").unwrap());
+        Arc::new(reports)
+    })
+}
+
+// Built once and cloned for each request, as axum does for every connection.
+fn app() -> axum::Router {
+    static APP: OnceLock<axum::Router> = OnceLock::new();
+    APP.get_or_init(|| router(reports().clone(), None)).clone()
+}
+
+async fn get(uri: &str) -> (StatusCode, serde_json::Value) {
+    let response = app()
+        .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    let status = response.status();
+    let bytes = response.into_body().collect().await.unwrap().to_bytes();
+    (status, serde_json::from_slice(&bytes).unwrap())
+}
+
+#[tokio::test]
+async fn lists_reports() {
+    let (status, body) = get("/reports").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body, serde_json::json!(["coolstore", "demo"]));
+}
+
+#[tokio::test]
+async fn impacted_files_and_summary() {
+    let (status, body) = get("/reports/coolstore/files").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body.as_array().unwrap().len(), 424);
+
+    let (status, body) = get("/reports/demo/summary").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["rulesets"], 1);
+    assert_eq!(body["impacted_files"], 20);
+}
+
+#[tokio::test]
+async fn violations_for_one_uri() {
+    let (status, body) = get("/reports/demo/files/violations?uri=file:///examples/customers-tomcat-legacy/pom.xml").await;
+    assert_eq!(status, StatusCode::OK);
+    // Fingerprints are the ones of the whole report, as exported.
+    let fingerprints: Vec<String> = body["konveyor-analysis"]["violations"]["xml-pom-001"]["incidents"].as_array().unwrap().iter()
+        .map(|incident| incident["fingerprint"].as_str().unwrap().to_string())
+        .collect();
+    let expected: Vec<String> = incident_records("demo", &reports()["demo"], None).into_iter()
+        .filter(|record| record.rule_id == "xml-pom-001" && record.uri.ends_with("customers-tomcat-legacy/pom.xml"))
+        .map(|record| record.fingerprint)
+        .collect();
+    assert_eq!(fingerprints, expected);
+    let rulesets: BTreeMap<String, Ruleset> = serde_json::from_value(body).unwrap();
+    let ruleset = &rulesets["konveyor-analysis"];
+    assert_eq!(ruleset.violations.len(), 2);
+    assert_eq!(ruleset.violations["xml-pom-001"].incidents.len(), 17);
+
+    let (status, _) = get("/reports/demo/files/violations?uri=file:///nope").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn related_files_of_one_uri() {
+    let (status, body) = get("/reports/demo/files/related?uri=file:///examples/java/pom.xml").await;
+    assert_eq!(status, StatusCode::OK);
+    let related: Vec<RelatedFile> = serde_json::from_value(body).unwrap();
+    let app = related.iter().find(|r| r.uri.ends_with("/apps/App.java")).unwrap();
+    assert_eq!(app.relations[0], Relation::Coordinates("io.fabric8:kubernetes-client".to_string()));
+
+    let (status, _) = get("/reports/demo/files/related?uri=file:///nope").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn rules_and_label_filter() {
+    let (status, body) = get("/reports/demo/rules").await;
+    assert_eq!(status, StatusCode::OK);
+    let rules: Vec<RuleEntry> = serde_json::from_value(body).unwrap();
+    let chain = rules.iter().find(|r| &*r.rule_id == "chain-pom-001").unwrap();
+    assert_eq!(chain.incidents, 25);
+
+    let label = "konveyor.io/target=quarkus";
+    let (status, body) = get("/reports/coolstore/violations?label=konveyor.io%2Ftarget%3Dquarkus").await;
+    assert_eq!(status, StatusCode::OK);
+    let mut incidents = body["rulesets"].as_array().unwrap().iter()
+        .flat_map(|ruleset| ruleset["violations"].as_object().unwrap().values())
+        .flat_map(|violation| violation["incidents"].as_array().unwrap());
+    assert!(incidents.all(|incident| incident["fingerprint"].as_str().unwrap().len() == 16));
+    let filtered: AnalysisReport = serde_json::from_value(body).unwrap();
+    let expected = reports()["coolstore"].filter_by_label(label);
+    assert_eq!(filtered.violation_counts(), expected.violation_counts());
+}
+
+#[tokio::test]
+async fn incidents_with_fingerprints() {
+    let (status, body) = get("/reports/demo/incidents").await;
+    assert_eq!(status, StatusCode::OK);
+    let incidents = body.as_array().unwrap();
+    assert_eq!(incidents.len(), 86);
+    let expected = incident_records("demo", &reports()["demo"], None);
+    assert_eq!(incidents[0]["fingerprint"], expected[0].fingerprint.as_str());
+    assert!(incidents.iter().all(|incident| incident["fingerprint"].as_str().unwrap().len() == 16));
+}
+
+#[tokio::test]
+async fn unknown_report_is_not_found() {
+    let (status, body) = get("/reports/missing/summary").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert!(body["error"].as_str().unwrap().contains("missing"));
+}